@@ -0,0 +1,57 @@
+use smart_wallet_interface::types::{Signatures, SignerKey, SignerLimits};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    Env, FromVal, Symbol,
+};
+
+use crate::signer::verify_signer_limit_keys;
+
+/// Can `signer_key`, limited by `signer_limits`, authorize `context`?
+/// Panics if it can but the limit's required keys or policies fail.
+pub fn verify_context(
+    env: &Env,
+    context: &Context,
+    signer_key: &SignerKey,
+    signer_limits: &SignerLimits,
+    signatures: &Signatures,
+) -> bool {
+    match &signer_limits.0 {
+        None => true, // No limits
+        Some(signer_limits) => {
+            if signer_limits.is_empty() {
+                return true;
+            }
+            match context {
+                Context::Contract(ContractContext {
+                    contract,
+                    fn_name,
+                    args,
+                }) => match signer_limits.get(contract.clone()) {
+                    None => false,
+                    Some(signer_limits_keys) => {
+                        // A limited signer scoped to this account may only remove itself
+                        if *contract == env.current_contract_address()
+                            && (*fn_name != Symbol::new(env, "remove_signer")
+                                || SignerKey::from_val(env, &args.get_unchecked(0)) != *signer_key)
+                        {
+                            return false;
+                        }
+
+                        verify_signer_limit_keys(env, signer_key, signatures, &signer_limits_keys, context);
+                        true
+                    }
+                },
+                // Only signers scoped to this account may deploy contracts
+                Context::CreateContractHostFn(_) | Context::CreateContractWithCtorHostFn(_) => {
+                    match signer_limits.get(env.current_contract_address()) {
+                        None => false,
+                        Some(signer_limits_keys) => {
+                            verify_signer_limit_keys(env, signer_key, signatures, &signer_limits_keys, context);
+                            true
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,125 +1,418 @@
 #![no_std]
 
 use soroban_sdk::{
-    auth::{Context, CustomAccountInterface},
-    contract, contractimpl, contracttype,
-    Address, BytesN, Env, Symbol, Vec, crypto::Hash,
-    panic_with_error,
+    auth::{Context, ContractContext, CustomAccountInterface, InvokerContractAuthEntry, SubContractInvocation},
+    contract, contracterror, contractimpl, contracttype,
+    Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec, crypto::Hash,
+    panic_with_error, symbol_short, token::TokenClient, vec,
 };
-use smart_wallet_interface::types::{
-    Error, Signature, Signatures, SignerKey, SignerVal, SignerStorage, Signer, SignerExpiration, SignerLimits, Secp256r1Signature
+use smart_wallet_interface::{
+    secp256k1,
+    types::{Error, Signature, Signatures, Signer, SignerKey, SignerStorage, SignerVal},
+    upgrade::{self, PendingRemoval, PendingUpgrade, SignerChange},
+    PolicyClient, SmartWalletInterface,
 };
 
-// --- Signer management and verification logic (adapted from smart-wallet) ---
+use context::verify_context;
+use signer::{get_signer_val_storage, process_signer, store_signer, verify_signer_expiration};
+use storage::extend_instance;
+use verify::{get_rp_id_hash, set_rp_id_hash, verify_secp256r1_signature};
 
-fn get_signer_val_storage(
-    env: &Env,
-    signer_key: &SignerKey,
-    _extend_ttl: bool,
-) -> Option<(SignerVal, SignerStorage)> {
-    match env.storage().temporary().get::<SignerKey, SignerVal>(signer_key) {
-        Some(signer_val) => Some((signer_val, SignerStorage::Temporary)),
-        None => env.storage().persistent().get::<SignerKey, SignerVal>(signer_key).map(|signer_val| (signer_val, SignerStorage::Persistent)),
+mod context;
+mod signer;
+mod storage;
+mod verify;
+
+#[cfg(test)]
+mod test;
+
+// --- Account contract ---
+
+#[contract]
+pub struct Account;
+
+#[contracttype]
+pub struct AccountSignature {
+    pub signature: BytesN<64>,
+}
+
+// --- Vault ---
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum VaultError {
+    InvalidAmount = 20,
+    InsufficientBalance = 21,
+    TokenLocked = 22,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum VaultKey {
+    Balance(Address), // token -> amount this account holds on its books
+    LpLock(Address),  // present while the token cannot be withdrawn
+}
+
+/// Mirror of the manager's `ClaimStatus`.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Claimed,
+    NotEnded,
+    NothingToClaim,
+    Failed(u32),
+}
+
+/// Mirror of the manager's `ClaimResult`, as returned by `claim_many`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimResult {
+    pub id: u32,
+    pub status: ClaimStatus,
+    pub flash: i128,
+    pub lp: i128,
+}
+
+fn get_balance(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&VaultKey::Balance(token.clone()))
+        .unwrap_or(0)
+}
+
+fn set_balance(env: &Env, token: &Address, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&VaultKey::Balance(token.clone()), &amount);
+}
+
+/// Brings the books for `token` in line with what the token says this account
+/// holds, picking up transfers that bypassed `deposit` and any clawback.
+fn reconcile(env: &Env, token: &Address) -> i128 {
+    let balance = TokenClient::new(env, token).balance(&env.current_contract_address());
+    set_balance(env, token, balance);
+    balance
+}
+
+fn debit(env: &Env, token: &Address, amount: i128) -> Result<(), VaultError> {
+    if amount <= 0 {
+        return Err(VaultError::InvalidAmount);
     }
+    let balance = reconcile(env, token);
+    if balance < amount {
+        return Err(VaultError::InsufficientBalance);
+    }
+    set_balance(env, token, balance - amount);
+    Ok(())
 }
 
-fn verify_signer_expiration(env: &Env, expiration: &SignerExpiration) {
-    if let Some(ledger) = expiration.0 {
-        let current_ledger = env.ledger().sequence();
-        if current_ledger > ledger {
-            panic_with_error!(env, Error::SignerExpired);
+const EVENT_TAG: Symbol = symbol_short!("sw_v1");
+const INITIALIZED: Symbol = symbol_short!("init");
+const OWNER: Symbol = symbol_short!("owner");
+
+fn get_owner(env: &Env) -> Address {
+    env.storage().instance().get(&OWNER).unwrap()
+}
+
+/// Deployed by the campaign manager for `owner`, the user it holds funds for.
+/// `signer` is the first signer; the owner can always put one back. `rp_id`
+/// is set up front so an account whose only signer is a passkey can sign.
+#[contractimpl]
+impl Account {
+    pub fn __constructor(env: Env, owner: Address, signer: Signer, rp_id: Bytes) {
+        env.storage().instance().set(&OWNER, &owner);
+        set_rp_id_hash(&env, &env.crypto().sha256(&rp_id).into());
+        Self::add_signer(env, signer);
+    }
+
+    pub fn owner(env: Env) -> Address {
+        get_owner(&env)
+    }
+
+    /// Adds or replaces `signer` with the owner's authorization instead of the
+    /// account's, e.g. after the only passkey is lost. Replacing a signer that
+    /// guards upgrades waits out `UPGRADE_DELAY`, as `update_signer` does, so
+    /// the account's signers can cancel it.
+    pub fn recover(env: Env, signer: Signer) {
+        get_owner(&env).require_auth();
+
+        let (signer_key, signer_val, signer_storage) = process_signer(signer.clone());
+        let previous = get_signer_val_storage(&env, &signer_key, false);
+        if let Some((previous_val, previous_storage)) = &previous {
+            if upgrade::change_is_timelocked(
+                &env,
+                previous_val,
+                previous_storage,
+                Some((&signer_val, &signer_storage)),
+            ) && !upgrade::removal_due(&env, &signer_key, &SignerChange::Update(signer))
+            {
+                return;
+            }
         }
+        let update = previous.is_some();
+        store_signer(&env, &signer_key, &signer_val, &signer_storage, update);
+        extend_instance(&env);
+
+        let action = if update { symbol_short!("update") } else { symbol_short!("add") };
+        env.events().publish(
+            (EVENT_TAG, action, signer_key),
+            (signer_val, signer_storage),
+        );
     }
 }
 
-fn verify_context(
-    env: &Env,
-    context: &Context,
-    signer_key: &SignerKey,
-    signer_limits: &SignerLimits,
-    signatures: &Signatures,
-) -> bool {
-    match &signer_limits.0 {
-        None => true, // No limits
-        Some(signer_limits) => {
-            if signer_limits.is_empty() {
-                return true;
-            }
-            match context {
-                Context::Contract(ctx) => {
-                    match signer_limits.get(ctx.contract.clone()) {
-                        None => false,
-                        Some(_signer_limits_keys) => {
-                            // For now, just allow if present. Expand for multisig/policy as needed.
-                            true
-                        }
-                    }
-                }
-                _ => false,
+/// Signer management, as in passkey-kit's smart wallet. The constructor seeds
+/// the first signer; after that every change needs the account's own auth.
+#[contractimpl]
+impl SmartWalletInterface for Account {
+
+    fn add_signer(env: Env, signer: Signer) {
+        if env.storage().instance().get::<Symbol, bool>(&INITIALIZED).unwrap_or(false) {
+            env.current_contract_address().require_auth();
+        } else {
+            env.storage().instance().set::<Symbol, bool>(&INITIALIZED, &true);
+        }
+
+        let (signer_key, signer_val, signer_storage) = process_signer(signer);
+        store_signer(&env, &signer_key, &signer_val, &signer_storage, false);
+        extend_instance(&env);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("add"), signer_key),
+            (signer_val, signer_storage),
+        );
+    }
+
+    fn update_signer(env: Env, signer: Signer) {
+        env.current_contract_address().require_auth();
+
+        let (signer_key, signer_val, signer_storage) = process_signer(signer.clone());
+        // changing an unlimited signer beyond extending it is timelocked too,
+        // `removal_due` emits the schedule
+        if let Some((previous_val, previous_storage)) =
+            get_signer_val_storage(&env, &signer_key, false)
+        {
+            if upgrade::change_is_timelocked(
+                &env,
+                &previous_val,
+                &previous_storage,
+                Some((&signer_val, &signer_storage)),
+            ) && !upgrade::removal_due(&env, &signer_key, &SignerChange::Update(signer))
+            {
+                return;
             }
         }
+        store_signer(&env, &signer_key, &signer_val, &signer_storage, true);
+        extend_instance(&env);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("update"), signer_key),
+            (signer_val, signer_storage),
+        );
     }
-}
 
-fn verify_secp256r1_signature(
-    env: &Env,
-    signature_payload: &Hash<32>,
-    public_key: &BytesN<65>,
-    signature: Secp256r1Signature,
-) {
-    // Minimal: just call the SDK's secp256r1_verify for now
-    env.crypto().secp256r1_verify(
-        public_key,
-        &env.crypto().sha256(&signature.authenticator_data),
-        &signature.signature,
-    );
-    // TODO: Add clientDataJson challenge check if needed
-}
+    fn remove_signer(env: Env, signer_key: SignerKey) {
+        env.current_contract_address().require_auth();
 
-// --- Account contract ---
+        let (signer_val, signer_storage) = get_signer_val_storage(&env, &signer_key, false)
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+        // signers that can cancel upgrades only leave once the timelock has passed
+        if upgrade::change_is_timelocked(&env, &signer_val, &signer_storage, None)
+            && !upgrade::removal_due(&env, &signer_key, &SignerChange::Remove)
+        {
+            return;
+        }
+        match signer_storage {
+            SignerStorage::Persistent => env.storage().persistent().remove::<SignerKey>(&signer_key),
+            SignerStorage::Temporary => env.storage().temporary().remove::<SignerKey>(&signer_key),
+        }
+        extend_instance(&env);
 
-#[contract]
-pub struct Account;
+        env.events().publish((EVENT_TAG, symbol_short!("remove"), signer_key), ());
+    }
 
-#[contracttype]
-pub struct AccountSignature {
-    pub signature: BytesN<64>,
+    fn schedule_upgrade(env: Env, hash: BytesN<32>, earliest_ledger: u32) {
+        env.current_contract_address().require_auth();
+        upgrade::schedule(&env, hash, earliest_ledger);
+    }
+
+    /// Runs the scheduled upgrade once its timelock has passed.
+    fn update_contract_code(env: Env, hash: BytesN<32>) {
+        env.current_contract_address().require_auth();
+        upgrade::execute(&env, hash);
+    }
+
+    fn cancel_upgrade(env: Env) {
+        env.current_contract_address().require_auth();
+        upgrade::cancel(&env);
+    }
+
+    fn cancel_removal(env: Env, signer_key: SignerKey) {
+        env.current_contract_address().require_auth();
+        upgrade::cancel_removal(&env, &signer_key);
+    }
 }
 
+/// Custodial vault: holds tokens and campaign LP and talks to the campaign
+/// manager as itself. Everything but `deposit` needs the account's own
+/// authorization, i.e. its signers.
 #[contractimpl]
 impl Account {
-    pub fn deposit(_env: Env, _from: Address, _token: Address, _amount: i128) {
-        // Transfer tokens from `from` to this contract.
+    pub fn deposit(env: Env, from: Address, token: Address, amount: i128) -> Result<i128, VaultError> {
+        if amount <= 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+        from.require_auth();
+        TokenClient::new(&env, &token).transfer(&from, &env.current_contract_address(), &amount);
+        let balance = get_balance(&env, &token) + amount;
+        set_balance(&env, &token, balance);
+        Ok(balance)
     }
 
-    pub fn withdraw(_env: Env, _to: Address, _token: Address, _amount: i128) {
-        // Transfer tokens from this contract to `to`.
+    pub fn withdraw(env: Env, to: Address, token: Address, amount: i128) -> Result<i128, VaultError> {
+        env.current_contract_address().require_auth();
+        if env.storage().instance().has(&VaultKey::LpLock(token.clone())) {
+            return Err(VaultError::TokenLocked);
+        }
+        debit(&env, &token, amount)?;
+        TokenClient::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        Ok(get_balance(&env, &token))
     }
 
+    /// Joins `campaign_id` with `amount` of the pair's token0 held here.
     pub fn join_campaign(
-        _env: Env,
-        _campaign_manager: Address,
-        _pool: Address,
-        _token: Address,
-        _amount: i128,
-    ) {
-        // Interact with campaign manager to join a campaign.
+        env: Env,
+        campaign_manager: Address,
+        campaign_id: u32,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), VaultError> {
+        env.current_contract_address().require_auth();
+        let this = env.current_contract_address();
+        debit(&env, &token, amount)?;
+
+        // the manager pulls the deposit from us one call deeper
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: token.clone(),
+                    fn_name: symbol_short!("transfer"),
+                    args: (this.clone(), campaign_manager.clone(), amount).into_val(&env),
+                },
+                sub_invocations: vec![&env],
+            }),
+        ]);
+        env.invoke_contract::<()>(
+            &campaign_manager,
+            &Symbol::new(&env, "join_campaign"),
+            vec![
+                &env,
+                campaign_id.into_val(&env),
+                amount.into_val(&env),
+                this.into_val(&env),
+                ().into_val(&env), // no allowlist proof
+            ],
+        );
+        Ok(())
     }
 
-    pub fn claim_campaign(
-        _env: Env,
-        _campaign_manager: Address,
-        _campaign_id: u32,
-    ) {
-        // Call campaign manager's claim function.
+    /// Claims `campaign_id` into this account and books the FLASH and LP
+    /// paid out under the tokens the manager reports for that campaign.
+    pub fn claim_campaign(env: Env, campaign_manager: Address, campaign_id: u32) -> ClaimResult {
+        env.current_contract_address().require_auth();
+        let this = env.current_contract_address();
+        let (reward_token, lp_token) = env.invoke_contract::<(Address, Address)>(
+            &campaign_manager,
+            &Symbol::new(&env, "payout_tokens"),
+            vec![&env, campaign_id.into_val(&env)],
+        );
+        let results = env.invoke_contract::<Vec<ClaimResult>>(
+            &campaign_manager,
+            &Symbol::new(&env, "claim_many"),
+            vec![
+                &env,
+                vec![&env, campaign_id].into_val(&env),
+                this.into_val(&env),
+                this.into_val(&env),
+            ],
+        );
+        let r = results.get_unchecked(0);
+        if r.flash > 0 {
+            set_balance(&env, &reward_token, get_balance(&env, &reward_token) + r.flash);
+        }
+        if r.lp > 0 {
+            set_balance(&env, &lp_token, get_balance(&env, &lp_token) + r.lp);
+        }
+        r
     }
 
-    pub fn set_lp_lock(_env: Env, _lp_token: Address, _locked: bool) {
-        // Lock/unlock LP tokens.
+    /// Hands the campaign position `token_id` held by this account to `to`.
+    pub fn transfer_position(env: Env, campaign_manager: Address, to: Address, token_id: u64) {
+        env.current_contract_address().require_auth();
+        env.invoke_contract::<()>(
+            &campaign_manager,
+            &Symbol::new(&env, "transfer_position"),
+            vec![
+                &env,
+                env.current_contract_address().into_val(&env),
+                to.into_val(&env),
+                token_id.into_val(&env),
+            ],
+        );
+    }
+
+    pub fn set_lp_lock(env: Env, lp_token: Address, locked: bool) {
+        env.current_contract_address().require_auth();
+        let key = VaultKey::LpLock(lp_token);
+        if locked {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+    }
+
+    pub fn balance(env: Env, token: Address) -> i128 {
+        get_balance(&env, &token)
+    }
+
+    pub fn is_locked(env: Env, token: Address) -> bool {
+        env.storage().instance().has(&VaultKey::LpLock(token))
+    }
+
+    /// Passkey assertions must carry sha256(`rp_id`) as their rpIdHash.
+    /// Starts out as the constructor's `rp_id`.
+    pub fn set_rp_id(env: Env, rp_id: Bytes) {
+        env.current_contract_address().require_auth();
+        set_rp_id_hash(&env, &env.crypto().sha256(&rp_id).into());
+    }
+
+    pub fn rp_id_hash(env: Env) -> Option<BytesN<32>> {
+        get_rp_id_hash(&env)
+    }
+
+    /// Restricts passkey assertions to these clientDataJSON origins, e.g. the web
+    /// app and its Android `android:apk-key-hash:` origin. Empty allows any.
+    pub fn set_origins(env: Env, origins: Vec<Bytes>) {
+        env.current_contract_address().require_auth();
+        verify::set_origins(&env, &origins);
+    }
+
+    pub fn origins(env: Env) -> Vec<Bytes> {
+        verify::get_origins(&env)
+    }
+
+    pub fn pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&env)
+    }
+
+    /// The scheduled change to `signer_key` and the earliest ledger it can go ahead.
+    pub fn pending_removal(env: Env, signer_key: SignerKey) -> Option<PendingRemoval> {
+        upgrade::get_pending_removal(&env, &signer_key)
     }
 }
 
-// Minimal CustomAccountInterface implementation
 #[contractimpl]
 impl CustomAccountInterface for Account {
     type Error = Error;
@@ -140,6 +433,7 @@ impl CustomAccountInterface for Account {
                             SignerVal::Policy(exp, lim) => (exp, lim),
                             SignerVal::Ed25519(exp, lim) => (exp, lim),
                             SignerVal::Secp256r1(_, exp, lim) => (exp, lim),
+                            SignerVal::Secp256k1(exp, lim) => (exp, lim),
                         };
                         verify_signer_expiration(&env, signer_expiration);
                         if verify_context(&env, &context, &signer_key, signer_limits, &signatures) {
@@ -180,14 +474,36 @@ impl CustomAccountInterface for Account {
                             }
                             panic_with_error!(env, Error::SignatureKeyValueMismatch)
                         }
+                        Signature::Secp256k1(sig) => {
+                            if let SignerKey::Secp256k1(key) = &signer_key {
+                                secp256k1::verify_signature(&env, &signature_payload, key, sig);
+                                if let SignerVal::Secp256k1(exp, _) = &signer_val {
+                                    verify_signer_expiration(&env, exp);
+                                }
+                                continue;
+                            }
+                            panic_with_error!(env, Error::SignatureKeyValueMismatch)
+                        }
                         Signature::Policy => {
-                            // Policy logic not implemented yet
+                            // Forward the whole context list to the policy contract
+                            if let SignerKey::Policy(policy) = &signer_key {
+                                PolicyClient::new(&env, policy).policy__(
+                                    &env.current_contract_address(),
+                                    &signer_key,
+                                    &auth_contexts,
+                                );
+                                if let SignerVal::Policy(exp, _) = &signer_val {
+                                    verify_signer_expiration(&env, exp);
+                                }
+                                continue;
+                            }
                             panic_with_error!(env, Error::SignatureKeyValueMismatch)
                         }
                     }
                 }
             }
         }
+        extend_instance(&env);
         Ok(())
     }
 }
\ No newline at end of file
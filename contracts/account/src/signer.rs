@@ -0,0 +1,202 @@
+use smart_wallet_interface::{
+    secp256k1,
+    types::{Error, Signatures, Signer, SignerExpiration, SignerKey, SignerStorage, SignerVal},
+    PolicyClient,
+};
+use soroban_sdk::{auth::Context, contracterror, panic_with_error, vec, Env, Vec};
+
+use crate::{context::verify_context, storage::extend_signer_key};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SignerError {
+    // signer groups are a smart-wallet feature the account doesn't have
+    GroupNotSupported = 50,
+}
+
+pub fn process_signer(signer: Signer) -> (SignerKey, SignerVal, SignerStorage) {
+    match signer {
+        Signer::Policy(policy, signer_expiration, signer_limits, signer_storage) => (
+            SignerKey::Policy(policy),
+            SignerVal::Policy(signer_expiration, signer_limits),
+            signer_storage,
+        ),
+        Signer::Ed25519(public_key, signer_expiration, signer_limits, signer_storage) => (
+            SignerKey::Ed25519(public_key),
+            SignerVal::Ed25519(signer_expiration, signer_limits),
+            signer_storage,
+        ),
+        Signer::Secp256r1(id, public_key, signer_expiration, signer_limits, signer_storage) => (
+            SignerKey::Secp256r1(id),
+            SignerVal::Secp256r1(public_key, signer_expiration, signer_limits),
+            signer_storage,
+        ),
+        Signer::Secp256k1(key, signer_expiration, signer_limits, signer_storage) => (
+            SignerKey::Secp256k1(key),
+            SignerVal::Secp256k1(signer_expiration, signer_limits),
+            signer_storage,
+        ),
+    }
+}
+
+pub fn store_signer(
+    env: &Env,
+    signer_key: &SignerKey,
+    signer_val: &SignerVal,
+    signer_storage: &SignerStorage,
+    update: bool,
+) {
+    if let SignerKey::Secp256k1(key) = signer_key {
+        secp256k1::verify_key(env, key);
+    }
+    let signer_limits = match signer_val {
+        SignerVal::Policy(_, limits)
+        | SignerVal::Ed25519(_, limits)
+        | SignerVal::Secp256r1(_, _, limits)
+        | SignerVal::Secp256k1(_, limits) => limits,
+    };
+    if let Some(limits) = &signer_limits.0 {
+        for keys in limits.values().iter().flatten() {
+            if keys.iter().any(|key| matches!(key, SignerKey::Group(_))) {
+                panic_with_error!(env, SignerError::GroupNotSupported);
+            }
+        }
+    }
+    // Include this before the `.set` calls so it doesn't read them as previous values
+    let previous_signer_val_and_storage: Option<(SignerVal, SignerStorage)> =
+        get_signer_val_storage(env, signer_key, false);
+
+    // Add and extend the signer key in the appropriate storage
+    let is_persistent = match signer_storage {
+        SignerStorage::Persistent => {
+            env.storage()
+                .persistent()
+                .set::<SignerKey, SignerVal>(signer_key, signer_val);
+
+            true
+        }
+        SignerStorage::Temporary => {
+            env.storage()
+                .temporary()
+                .set::<SignerKey, SignerVal>(signer_key, signer_val);
+
+            false
+        }
+    };
+
+    extend_signer_key(env, signer_key, is_persistent);
+
+    match previous_signer_val_and_storage {
+        Some((_, previous_signer_storage)) => {
+            // Panic if the signer key already exists and we're not update it
+            if !update {
+                panic_with_error!(env, Error::AlreadyExists);
+            }
+
+            // Remove signer key in the opposing storage if it exists
+            match previous_signer_storage {
+                SignerStorage::Persistent => {
+                    if !is_persistent {
+                        env.storage().persistent().remove::<SignerKey>(signer_key);
+                    }
+                }
+                SignerStorage::Temporary => {
+                    if is_persistent {
+                        env.storage().temporary().remove::<SignerKey>(signer_key);
+                    }
+                }
+            }
+        }
+        None => {
+            // Panic if we're update a signer key that doesn't exist
+            if update {
+                panic_with_error!(env, Error::NotFound);
+            }
+        }
+    }
+}
+
+pub fn get_signer_val_storage(
+    env: &Env,
+    signer_key: &SignerKey,
+    extend_ttl: bool,
+) -> Option<(SignerVal, SignerStorage)> {
+    match env
+        .storage()
+        .temporary()
+        .get::<SignerKey, SignerVal>(signer_key)
+    {
+        Some(signer_val) => {
+            if extend_ttl {
+                extend_signer_key(env, signer_key, false);
+            }
+
+            Some((signer_val, SignerStorage::Temporary))
+        }
+        None => {
+            match env
+                .storage()
+                .persistent()
+                .get::<SignerKey, SignerVal>(signer_key)
+            {
+                Some(signer_val) => {
+                    if extend_ttl {
+                        extend_signer_key(env, signer_key, true);
+                    }
+
+                    Some((signer_val, SignerStorage::Persistent))
+                }
+                None => None,
+            }
+        }
+    }
+}
+
+pub fn verify_signer_expiration(env: &Env, expiration: &SignerExpiration) {
+    if let Some(ledger) = expiration.0 {
+        let current_ledger = env.ledger().sequence();
+        if current_ledger > ledger {
+            panic_with_error!(env, Error::SignerExpired);
+        }
+    }
+}
+
+/// Every key listed in a signer's limits for this context must also sign,
+/// except policies, which instead run their own `policy__` check.
+pub fn verify_signer_limit_keys(
+    env: &Env,
+    signer_key: &SignerKey,
+    signatures: &Signatures,
+    signer_limits_keys: &Option<Vec<SignerKey>>,
+    context: &Context,
+) {
+    if let Some(signer_limits_keys) = signer_limits_keys {
+        for signer_limits_key in signer_limits_keys.iter() {
+            if let SignerKey::Policy(policy) = &signer_limits_key {
+                // A policy stored on this account must also pass its own limits.
+                // NOTE a policy that lists itself here recurses forever
+                if let Some((SignerVal::Policy(signer_expiration, signer_limits), _)) =
+                    get_signer_val_storage(env, &signer_limits_key, true)
+                {
+                    verify_signer_expiration(env, &signer_expiration);
+
+                    if !verify_context(env, context, &signer_limits_key, &signer_limits, signatures) {
+                        panic_with_error!(env, Error::FailedPolicySignerLimits)
+                    }
+                }
+
+                PolicyClient::new(env, policy).policy__(
+                    &env.current_contract_address(),
+                    signer_key,
+                    &vec![env, context.clone()],
+                );
+            } else if let SignerKey::Group(_) = &signer_limits_key {
+                // refused by `store_signer`; fails closed for signers stored before that
+                panic_with_error!(env, SignerError::GroupNotSupported)
+            } else if !signatures.0.contains_key(signer_limits_key.clone()) {
+                panic_with_error!(env, Error::FailedSignerLimits)
+            }
+        }
+    }
+}
@@ -0,0 +1,658 @@
+#![cfg(test)]
+extern crate std;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use p256::ecdsa::{signature::Signer as _, Signature as P256Signature, SigningKey};
+use smart_wallet_interface::{
+    types::{
+        Error, Secp256r1Signature, Signature, Signatures, Signer, SignerExpiration, SignerKey,
+        SignerLimits, SignerStorage,
+    },
+    upgrade::{PendingRemoval, SignerChange, UpgradeError, UPGRADE_DELAY},
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    map, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    token::{StellarAssetClient, TokenClient},
+    vec, Address, Bytes, BytesN, Env, Error as SdkError, IntoVal, InvokeError, Symbol, Vec,
+};
+
+use crate::{
+    signer::SignerError, verify::WebAuthnError, Account, AccountClient, ClaimStatus, VaultError,
+};
+
+mod manager {
+    use soroban_sdk::{
+        contract, contractimpl, contracttype, token::TokenClient, vec, Address, Env, Val, Vec,
+    };
+
+    use crate::{ClaimResult, ClaimStatus};
+
+    pub const REWARD: i128 = 100;
+
+    #[contracttype]
+    enum Key {
+        Tokens,           // (token0, reward token, LP token)
+        Deposit(Address), // token0 escrowed for a user, paid back 1:1 in LP
+        Owner(u64),       // position holder by token id
+    }
+
+    /// Just enough of the campaign manager for the vault: joins escrow token0
+    /// and a claim pays `REWARD` plus the deposit back as LP.
+    #[contract]
+    pub struct MockManager;
+
+    #[contractimpl]
+    impl MockManager {
+        pub fn __constructor(e: Env, token0: Address, reward: Address, lp: Address) {
+            e.storage()
+                .instance()
+                .set(&Key::Tokens, &(token0, reward, lp));
+        }
+
+        pub fn join_campaign(e: Env, id: u32, amount: i128, user: Address, _proof: Option<Val>) {
+            user.require_auth();
+            let (token0, _, _): (Address, Address, Address) =
+                e.storage().instance().get(&Key::Tokens).unwrap();
+            TokenClient::new(&e, &token0).transfer(&user, &e.current_contract_address(), &amount);
+            let key = Key::Deposit(user.clone());
+            let deposit: i128 = e.storage().instance().get(&key).unwrap_or(0);
+            e.storage().instance().set(&key, &(deposit + amount));
+            e.storage().instance().set(&Key::Owner(id as u64), &user);
+        }
+
+        pub fn payout_tokens(e: Env, _id: u32) -> (Address, Address) {
+            let (_, reward, lp): (Address, Address, Address) =
+                e.storage().instance().get(&Key::Tokens).unwrap();
+            (reward, lp)
+        }
+
+        pub fn claim_many(
+            e: Env,
+            ids: Vec<u32>,
+            user: Address,
+            recipient: Address,
+        ) -> Vec<ClaimResult> {
+            user.require_auth();
+            let id = ids.get_unchecked(0);
+            let key = Key::Deposit(user);
+            let lp: i128 = e.storage().instance().get(&key).unwrap_or(0);
+            if lp == 0 {
+                return vec![
+                    &e,
+                    ClaimResult {
+                        id,
+                        status: ClaimStatus::NothingToClaim,
+                        flash: 0,
+                        lp: 0,
+                    },
+                ];
+            }
+            e.storage().instance().remove(&key);
+            let (reward, lp_token) = Self::payout_tokens(e.clone(), id);
+            let this = e.current_contract_address();
+            TokenClient::new(&e, &reward).transfer(&this, &recipient, &REWARD);
+            TokenClient::new(&e, &lp_token).transfer(&this, &recipient, &lp);
+            vec![
+                &e,
+                ClaimResult {
+                    id,
+                    status: ClaimStatus::Claimed,
+                    flash: REWARD,
+                    lp,
+                },
+            ]
+        }
+
+        pub fn transfer_position(e: Env, from: Address, to: Address, token_id: u64) {
+            from.require_auth();
+            e.storage().instance().set(&Key::Owner(token_id), &to);
+        }
+
+        pub fn owner_of(e: Env, token_id: u64) -> Option<Address> {
+            e.storage().instance().get(&Key::Owner(token_id))
+        }
+    }
+}
+use manager::{MockManager, MockManagerClient, REWARD};
+
+mod policy {
+    use smart_wallet_interface::types::SignerKey;
+    use soroban_sdk::{
+        auth::{Context, ContractContext},
+        contract, contracterror, contractimpl, panic_with_error, symbol_short, Address, Env, Vec,
+    };
+
+    #[contracterror]
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    #[repr(u32)]
+    pub enum PolicyError {
+        Denied = 100,
+    }
+
+    /// Signs off on everything but `burn`.
+    #[contract]
+    pub struct MockPolicy;
+
+    #[contractimpl]
+    impl MockPolicy {
+        pub fn policy__(e: Env, _source: Address, _signer: SignerKey, contexts: Vec<Context>) {
+            for context in contexts.iter() {
+                if let Context::Contract(ContractContext { fn_name, .. }) = context {
+                    if fn_name == symbol_short!("burn") {
+                        panic_with_error!(e, PolicyError::Denied);
+                    }
+                }
+            }
+        }
+    }
+}
+use policy::{MockPolicy, PolicyError};
+
+const RP_ID: &[u8] = b"flashpool.app";
+
+fn register(e: &Env, owner: &Address, signer: Signer) -> Address {
+    e.register(Account, (owner.clone(), signer, Bytes::from_slice(e, RP_ID)))
+}
+
+fn test_signer(e: &Env, seed: u8) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(e, &[seed; 32]),
+        SignerExpiration(None),
+        SignerLimits(None),
+        SignerStorage::Persistent,
+    )
+}
+
+fn policy_signer(policy: &Address, limits: SignerLimits) -> Signer {
+    Signer::Policy(
+        policy.clone(),
+        SignerExpiration(None),
+        limits,
+        SignerStorage::Persistent,
+    )
+}
+
+fn call(e: &Env, contract: &Address, fn_name: Symbol) -> Context {
+    Context::Contract(ContractContext {
+        contract: contract.clone(),
+        fn_name,
+        args: vec![e],
+    })
+}
+
+/// Runs the account's `__check_auth` with just the policy signing.
+fn check_policy(
+    e: &Env,
+    acct: &Address,
+    policy: &Address,
+    contexts: Vec<Context>,
+) -> Result<(), Result<Error, InvokeError>> {
+    e.try_invoke_contract_check_auth::<Error>(
+        acct,
+        &BytesN::from_array(e, &[7; 32]),
+        Signatures(map![e, (SignerKey::Policy(policy.clone()), Signature::Policy)]).into_val(e),
+        &contexts,
+    )
+}
+
+fn token(e: &Env, admin: &Address) -> Address {
+    e.register_stellar_asset_contract_v2(admin.clone())
+        .address()
+}
+
+struct Setup<'a> {
+    e: Env,
+    ac: AccountClient<'a>,
+    mgr: MockManagerClient<'a>,
+    alice: Address,
+    t0: TokenClient<'a>,
+    flash: TokenClient<'a>,
+    lp: TokenClient<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let e = Env::default();
+    e.mock_all_auths();
+    let god = Address::generate(&e);
+    let alice = Address::generate(&e);
+    let (t0, flash, lp) = (token(&e, &god), token(&e, &god), token(&e, &god));
+    let mgr = e.register(MockManager, (t0.clone(), flash.clone(), lp.clone()));
+    StellarAssetClient::new(&e, &t0).mint(&alice, &10_000);
+    StellarAssetClient::new(&e, &flash).mint(&mgr, &10_000);
+    StellarAssetClient::new(&e, &lp).mint(&mgr, &10_000);
+    let ac = AccountClient::new(&e, &register(&e, &alice, test_signer(&e, 1)));
+    Setup {
+        ac,
+        mgr: MockManagerClient::new(&e, &mgr),
+        alice,
+        t0: TokenClient::new(&e, &t0),
+        flash: TokenClient::new(&e, &flash),
+        lp: TokenClient::new(&e, &lp),
+        e,
+    }
+}
+
+#[test]
+fn test_vault_join_and_claim() {
+    let Setup {
+        e,
+        ac,
+        mgr,
+        alice,
+        t0,
+        flash,
+        lp,
+    } = setup();
+    let acct = ac.address.clone();
+
+    assert_eq!(ac.deposit(&alice, &t0.address, &1_000), 1_000);
+    assert_eq!(t0.balance(&acct), 1_000);
+    assert_eq!(
+        ac.try_join_campaign(&mgr.address, &1, &t0.address, &1_500),
+        Err(Ok(VaultError::InsufficientBalance))
+    );
+    ac.join_campaign(&mgr.address, &1, &t0.address, &600);
+    assert_eq!(ac.balance(&t0.address), 400);
+    assert_eq!(t0.balance(&acct), 400);
+    assert_eq!(t0.balance(&mgr.address), 600);
+
+    // the payout is booked under the tokens the manager names
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let r = ac.claim_campaign(&mgr.address, &1);
+    assert_eq!(r.status, ClaimStatus::Claimed);
+    assert_eq!((r.flash, r.lp), (REWARD, 600));
+    assert_eq!(ac.balance(&flash.address), REWARD);
+    assert_eq!(flash.balance(&acct), REWARD);
+    assert_eq!(ac.balance(&lp.address), 600);
+    assert_eq!(
+        ac.claim_campaign(&mgr.address, &1).status,
+        ClaimStatus::NothingToClaim
+    );
+
+    ac.set_lp_lock(&lp.address, &true);
+    assert!(ac.is_locked(&lp.address));
+    assert_eq!(
+        ac.try_withdraw(&alice, &lp.address, &600),
+        Err(Ok(VaultError::TokenLocked))
+    );
+    ac.set_lp_lock(&lp.address, &false);
+    assert_eq!(ac.withdraw(&alice, &lp.address, &600), 0);
+    assert_eq!(lp.balance(&alice), 600);
+}
+
+#[test]
+fn test_withdraw_reconciles_books() {
+    let Setup {
+        e, ac, alice, t0, ..
+    } = setup();
+    let acct = ac.address.clone();
+    let bob = Address::generate(&e);
+
+    // sent straight to the account, bypassing deposit
+    ac.deposit(&alice, &t0.address, &100);
+    t0.transfer(&alice, &acct, &50);
+    assert_eq!(ac.balance(&t0.address), 100);
+    assert_eq!(ac.withdraw(&bob, &t0.address, &150), 0);
+    assert_eq!(t0.balance(&bob), 150);
+
+    // the books never promise more than the token holds
+    ac.deposit(&alice, &t0.address, &100);
+    t0.transfer(&acct, &alice, &40);
+    assert_eq!(
+        ac.try_withdraw(&bob, &t0.address, &100),
+        Err(Ok(VaultError::InsufficientBalance))
+    );
+    assert_eq!(ac.withdraw(&bob, &t0.address, &60), 0);
+    assert_eq!(
+        ac.try_withdraw(&bob, &t0.address, &0),
+        Err(Ok(VaultError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_transfer_position() {
+    let Setup {
+        e,
+        ac,
+        mgr,
+        alice,
+        t0,
+        ..
+    } = setup();
+    let bob = Address::generate(&e);
+
+    ac.deposit(&alice, &t0.address, &500);
+    ac.join_campaign(&mgr.address, &7, &t0.address, &500);
+    assert_eq!(mgr.owner_of(&7), Some(ac.address.clone()));
+    ac.transfer_position(&mgr.address, &bob, &7);
+    assert_eq!(e.auths()[0].0, ac.address);
+    assert_eq!(mgr.owner_of(&7), Some(bob));
+}
+
+#[test]
+fn test_signer_management() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let owner = Address::generate(&e);
+    let acct = register(&e, &owner, test_signer(&e, 1));
+    let ac = AccountClient::new(&e, &acct);
+    let key = |seed: u8| SignerKey::Ed25519(BytesN::from_array(&e, &[seed; 32]));
+    assert_eq!(ac.owner(), owner);
+
+    // the constructor's signer is in place, so a second add is a duplicate
+    assert_eq!(
+        ac.try_add_signer(&test_signer(&e, 1)),
+        Err(Ok(SdkError::from_contract_error(2)))
+    );
+
+    // later changes need the account's own authorization and emit sw_v1 events
+    ac.add_signer(&test_signer(&e, 2));
+    assert_eq!(e.auths()[0].0, acct);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("add"), key(2)).into_val(&e)
+    );
+
+    // expiring an unlimited signer waits out the upgrade delay
+    let expiring = Signer::Ed25519(
+        BytesN::from_array(&e, &[2; 32]),
+        SignerExpiration(Some(1_000)),
+        SignerLimits(None),
+        SignerStorage::Temporary,
+    );
+    let due = e.ledger().sequence() + UPGRADE_DELAY;
+    ac.update_signer(&expiring);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("removal"), symbol_short!("schedule")).into_val(&e)
+    );
+    assert_eq!(
+        ac.pending_removal(&key(2)),
+        Some(PendingRemoval { earliest_ledger: due, change: SignerChange::Update(expiring.clone()) })
+    );
+    assert_eq!(
+        ac.try_update_signer(&expiring),
+        Err(Ok(SdkError::from_contract_error(UpgradeError::TooEarly as u32)))
+    );
+    ac.cancel_removal(&key(2));
+    assert_eq!(ac.pending_removal(&key(2)), None);
+    assert_eq!(
+        ac.try_cancel_removal(&key(2)),
+        Err(Ok(SdkError::from_contract_error(UpgradeError::NoRemoval as u32)))
+    );
+
+    ac.update_signer(&expiring);
+    e.ledger().with_mut(|li| li.sequence_number += UPGRADE_DELAY);
+    ac.update_signer(&expiring);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("update"), key(2)).into_val(&e)
+    );
+    assert_eq!(ac.pending_removal(&key(2)), None);
+    assert_eq!(
+        ac.try_update_signer(&test_signer(&e, 3)),
+        Err(Ok(SdkError::from_contract_error(1)))
+    );
+
+    ac.remove_signer(&key(2));
+    assert_eq!(e.auths()[0].0, acct);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("remove"), key(2)).into_val(&e)
+    );
+    assert_eq!(
+        ac.try_remove_signer(&key(2)),
+        Err(Ok(SdkError::from_contract_error(1)))
+    );
+
+    // smart-wallet signer groups have no meaning here
+    let grouped = Signer::Ed25519(
+        BytesN::from_array(&e, &[3; 32]),
+        SignerExpiration(None),
+        SignerLimits(Some(map![
+            &e,
+            (acct.clone(), Some(vec![&e, SignerKey::Group(symbol_short!("team"))]))
+        ])),
+        SignerStorage::Persistent,
+    );
+    assert_eq!(
+        ac.try_add_signer(&grouped),
+        Err(Ok(SdkError::from_contract_error(SignerError::GroupNotSupported as u32)))
+    );
+
+    // the owner can put a signer back without the account's signers
+    ac.recover(&test_signer(&e, 2));
+    assert_eq!(e.auths()[0].0, owner);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("add"), key(2)).into_val(&e)
+    );
+    ac.recover(&test_signer(&e, 2));
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("update"), key(2)).into_val(&e)
+    );
+
+    // but replacing one that guards upgrades waits, and the signers can cancel
+    let limited = Signer::Ed25519(
+        BytesN::from_array(&e, &[2; 32]),
+        SignerExpiration(None),
+        SignerLimits(Some(map![&e, (acct.clone(), None)])),
+        SignerStorage::Persistent,
+    );
+    ac.recover(&limited);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("removal"), symbol_short!("schedule")).into_val(&e)
+    );
+    ac.cancel_removal(&key(2));
+    assert_eq!(e.auths()[0].0, acct);
+    ac.recover(&limited);
+    e.ledger().with_mut(|li| li.sequence_number += UPGRADE_DELAY);
+    ac.recover(&limited);
+    let (_, topics, _) = e.events().all().last().unwrap();
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("update"), key(2)).into_val(&e)
+    );
+}
+
+#[test]
+fn test_upgrade_timelock() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let owner = Address::generate(&e);
+    let ac = AccountClient::new(&e, &register(&e, &owner, test_signer(&e, 1)));
+    let hash = BytesN::from_array(&e, &[9; 32]);
+    let earliest = e.ledger().sequence() + 17_280;
+    let err = |code: UpgradeError| Err(Ok(SdkError::from_contract_error(code as u32)));
+
+    // upgrades wait at least a day and can be called off meanwhile
+    assert_eq!(
+        ac.try_schedule_upgrade(&hash, &(earliest - 1)),
+        err(UpgradeError::TooEarly)
+    );
+    ac.schedule_upgrade(&hash, &earliest);
+    assert_eq!(ac.pending_upgrade().unwrap().earliest_ledger, earliest);
+    assert_eq!(
+        ac.try_update_contract_code(&hash),
+        err(UpgradeError::TooEarly)
+    );
+    ac.cancel_upgrade();
+    assert_eq!(ac.pending_upgrade(), None);
+    e.ledger().with_mut(|li| li.sequence_number = earliest);
+    assert_eq!(
+        ac.try_update_contract_code(&hash),
+        err(UpgradeError::NoUpgrade)
+    );
+}
+
+#[test]
+fn test_policy_signer() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let acct = register(&e, &Address::generate(&e), test_signer(&e, 1));
+    let ac = AccountClient::new(&e, &acct);
+    let policy = e.register(MockPolicy, ());
+    let (pool, other) = (Address::generate(&e), Address::generate(&e));
+    let transfer = || call(&e, &pool, symbol_short!("transfer"));
+
+    // an unknown policy authorizes nothing
+    assert_eq!(
+        check_policy(&e, &acct, &policy, vec![&e, transfer()]),
+        Err(Ok(Error::MissingContext))
+    );
+    ac.add_signer(&policy_signer(&policy, SignerLimits(None)));
+    assert_eq!(check_policy(&e, &acct, &policy, vec![&e, transfer()]), Ok(()));
+
+    // the policy sees every context and can veto any of them
+    assert_eq!(
+        check_policy(
+            &e,
+            &acct,
+            &policy,
+            vec![&e, transfer(), call(&e, &pool, symbol_short!("burn"))]
+        ),
+        Err(Err(InvokeError::Contract(PolicyError::Denied as u32)))
+    );
+
+    // limited to `pool` and the account, once the upgrade delay has passed,
+    // it cannot sign for anything else
+    let limited = policy_signer(
+        &policy,
+        SignerLimits(Some(map![&e, (pool.clone(), None), (acct.clone(), None)])),
+    );
+    ac.update_signer(&limited);
+    e.as_contract(&policy, || {
+        let max_ttl = e.storage().max_ttl();
+        e.storage().instance().extend_ttl(max_ttl, max_ttl)
+    });
+    e.ledger().with_mut(|li| li.sequence_number += UPGRADE_DELAY);
+    ac.update_signer(&limited);
+    assert_eq!(check_policy(&e, &acct, &policy, vec![&e, transfer()]), Ok(()));
+    assert_eq!(
+        check_policy(
+            &e,
+            &acct,
+            &policy,
+            vec![&e, call(&e, &other, symbol_short!("transfer"))]
+        ),
+        Err(Ok(Error::MissingContext))
+    );
+    // nor manage the account beyond removing itself
+    assert_eq!(
+        check_policy(
+            &e,
+            &acct,
+            &policy,
+            vec![&e, call(&e, &acct, Symbol::new(&e, "add_signer"))]
+        ),
+        Err(Ok(Error::MissingContext))
+    );
+    let remove_self = Context::Contract(ContractContext {
+        contract: acct.clone(),
+        fn_name: Symbol::new(&e, "remove_signer"),
+        args: vec![&e, SignerKey::Policy(policy.clone()).into_val(&e)],
+    });
+    assert_eq!(check_policy(&e, &acct, &policy, vec![&e, remove_self]), Ok(()));
+}
+
+/// A WebAuthn assertion over `payload` with the given rpIdHash and flags.
+fn passkey_sign(
+    e: &Env,
+    key: &SigningKey,
+    rp_id_hash: [u8; 32],
+    flags: u8,
+    payload: &[u8; 32],
+) -> Signature {
+    let client_data_json = std::format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://flashpool.app"}}"#,
+        URL_SAFE_NO_PAD.encode(payload)
+    );
+    let mut authenticator_data = std::vec::Vec::from(rp_id_hash);
+    authenticator_data.extend_from_slice(&[flags, 0, 0, 0, 1]);
+    let mut message = authenticator_data.clone();
+    message.extend_from_slice(
+        &e.crypto()
+            .sha256(&Bytes::from_slice(e, client_data_json.as_bytes()))
+            .to_array(),
+    );
+    let signature: P256Signature = key.sign(&message);
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    Signature::Secp256r1(Secp256r1Signature {
+        authenticator_data: Bytes::from_slice(e, &authenticator_data),
+        client_data_json: Bytes::from_slice(e, client_data_json.as_bytes()),
+        signature: BytesN::from_array(e, &signature.to_bytes().into()),
+    })
+}
+
+#[test]
+fn test_passkey_assertion() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let key = SigningKey::from_bytes(&[1; 32].into()).unwrap();
+    let id = Bytes::from_array(&e, &[1, 2]);
+    let public_key = key.verifying_key().to_encoded_point(false);
+    let passkey = Signer::Secp256r1(
+        id.clone(),
+        BytesN::from_array(&e, public_key.as_bytes().try_into().unwrap()),
+        SignerExpiration(None),
+        SignerLimits(None),
+        SignerStorage::Persistent,
+    );
+    // a passkey as the only signer works straight away
+    let acct = register(&e, &Address::generate(&e), passkey);
+    let ac = AccountClient::new(&e, &acct);
+    let rp_id_hash = e.crypto().sha256(&Bytes::from_slice(&e, RP_ID)).to_array();
+    let payload = [7; 32];
+    let check = |signature: Signature| {
+        e.try_invoke_contract_check_auth::<Error>(
+            &acct,
+            &BytesN::from_array(&e, &payload),
+            Signatures(map![&e, (SignerKey::Secp256r1(id.clone()), signature)]).into_val(&e),
+            &vec![&e, call(&e, &Address::generate(&e), symbol_short!("transfer"))],
+        )
+    };
+    let err = |code: WebAuthnError| Err(Err(InvokeError::Contract(code as u32)));
+    const UP_UV: u8 = 0x05;
+
+    assert_eq!(ac.rp_id_hash(), Some(BytesN::from_array(&e, &rp_id_hash)));
+    assert_eq!(check(passkey_sign(&e, &key, rp_id_hash, UP_UV, &payload)), Ok(()));
+
+    // registered under another relying party
+    assert_eq!(
+        check(passkey_sign(&e, &key, [9; 32], UP_UV, &payload)),
+        err(WebAuthnError::RpIdHashMismatch)
+    );
+    // the user must be both present and verified
+    assert_eq!(
+        check(passkey_sign(&e, &key, rp_id_hash, 0x04, &payload)),
+        err(WebAuthnError::UserNotPresent)
+    );
+    assert_eq!(
+        check(passkey_sign(&e, &key, rp_id_hash, 0x01, &payload)),
+        err(WebAuthnError::UserNotVerified)
+    );
+    // an assertion over some other payload
+    assert_eq!(
+        check(passkey_sign(&e, &key, rp_id_hash, UP_UV, &[8; 32])),
+        Err(Ok(Error::ClientDataJsonChallengeIncorrect))
+    );
+
+    // moving to another relying party invalidates the old one
+    ac.set_rp_id(&Bytes::from_slice(&e, b"wallet.flashpool.app"));
+    assert_eq!(
+        check(passkey_sign(&e, &key, rp_id_hash, UP_UV, &payload)),
+        err(WebAuthnError::RpIdHashMismatch)
+    );
+}
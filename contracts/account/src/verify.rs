@@ -0,0 +1,122 @@
+use smart_wallet_interface::{
+    base64_url, client_data,
+    types::{Error, Secp256r1Signature},
+};
+use soroban_sdk::{
+    contracterror, contracttype, crypto::Hash, panic_with_error, Bytes, BytesN, Env, Vec,
+};
+
+/// WebAuthn assertion failures beyond the interface's JSON and challenge errors.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum WebAuthnError {
+    AuthenticatorDataTooShort = 23,
+    InvalidClientDataType = 24,
+    RpIdNotSet = 25,
+    RpIdHashMismatch = 26,
+    UserNotPresent = 27,
+    UserNotVerified = 28,
+    OriginNotAllowed = 29,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum WebAuthnKey {
+    RpIdHash, // sha256 of the relying party id passkeys are registered under
+    Origins,  // clientDataJSON origins passkeys may sign from, any when unset
+}
+
+// authenticatorData = rpIdHash (32) || flags (1) || signCount (4) || ...
+const RP_ID_HASH_LEN: u32 = 32;
+const FLAGS_INDEX: u32 = 32;
+const MIN_AUTH_DATA_LEN: u32 = 37;
+const FLAG_UP: u8 = 0x01;
+const FLAG_UV: u8 = 0x04;
+
+pub fn get_rp_id_hash(env: &Env) -> Option<BytesN<32>> {
+    env.storage().instance().get(&WebAuthnKey::RpIdHash)
+}
+
+pub fn set_rp_id_hash(env: &Env, rp_id_hash: &BytesN<32>) {
+    env.storage().instance().set(&WebAuthnKey::RpIdHash, rp_id_hash);
+}
+
+pub fn get_origins(env: &Env) -> Vec<Bytes> {
+    env.storage().instance().get(&WebAuthnKey::Origins).unwrap_or(Vec::new(env))
+}
+
+pub fn set_origins(env: &Env, origins: &Vec<Bytes>) {
+    if origins.is_empty() {
+        env.storage().instance().remove(&WebAuthnKey::Origins);
+    } else {
+        env.storage().instance().set(&WebAuthnKey::Origins, origins);
+    }
+}
+
+/// Full WebAuthn assertion check: the passkey signed
+/// `authenticatorData || sha256(clientDataJSON)`, the client data is a
+/// `webauthn.get` for `signature_payload`, and the authenticator data is for
+/// this account's relying party with the user present and verified.
+pub fn verify_secp256r1_signature(
+    env: &Env,
+    signature_payload: &Hash<32>,
+    public_key: &BytesN<65>,
+    signature: Secp256r1Signature,
+) {
+    let Secp256r1Signature {
+        authenticator_data,
+        client_data_json,
+        signature,
+    } = signature;
+
+    if authenticator_data.len() < MIN_AUTH_DATA_LEN {
+        panic_with_error!(env, WebAuthnError::AuthenticatorDataTooShort);
+    }
+
+    let mut message = authenticator_data.clone();
+    message.extend_from_array(&env.crypto().sha256(&client_data_json).to_array());
+
+    env.crypto()
+        .secp256r1_verify(public_key, &env.crypto().sha256(&message), &signature);
+
+    // Client data: must be an assertion over the expected base64url challenge
+    let client_data = client_data::parse(&client_data_json)
+        .unwrap_or_else(|| panic_with_error!(env, Error::JsonParseError));
+    if client_data.type_ != Some(Bytes::from_slice(env, b"webauthn.get")) {
+        panic_with_error!(env, WebAuthnError::InvalidClientDataType);
+    }
+
+    let mut expected_challenge = [0u8; 43];
+    base64_url::encode(&mut expected_challenge, &signature_payload.to_array());
+
+    if client_data.challenge != Some(Bytes::from_array(env, &expected_challenge)) {
+        panic_with_error!(env, Error::ClientDataJsonChallengeIncorrect);
+    }
+
+    let origins = get_origins(env);
+    if !origins.is_empty() && !client_data.origin.is_some_and(|origin| origins.contains(&origin)) {
+        panic_with_error!(env, WebAuthnError::OriginNotAllowed);
+    }
+
+    // Authenticator data: right relying party, user present and verified
+    let rp_id_hash = get_rp_id_hash(env)
+        .unwrap_or_else(|| panic_with_error!(env, WebAuthnError::RpIdNotSet));
+    let signed_rp_id_hash: BytesN<32> = authenticator_data
+        .slice(..RP_ID_HASH_LEN)
+        .try_into()
+        .unwrap_or_else(|_| panic_with_error!(env, WebAuthnError::AuthenticatorDataTooShort));
+
+    if signed_rp_id_hash != rp_id_hash {
+        panic_with_error!(env, WebAuthnError::RpIdHashMismatch);
+    }
+
+    let flags = authenticator_data.get_unchecked(FLAGS_INDEX);
+
+    if flags & FLAG_UP == 0 {
+        panic_with_error!(env, WebAuthnError::UserNotPresent);
+    }
+    if flags & FLAG_UV == 0 {
+        panic_with_error!(env, WebAuthnError::UserNotVerified);
+    }
+}
@@ -1,10 +1,20 @@
 #![no_std]
 
+//! Rolling-window spend limits for policy signers.
+//!
+//! Each wallet sets, per token contract and per function, the most it may
+//! move within any `window` ledgers and optionally which addresses may
+//! receive it. `policy__` rejects any context without such a limit.
+//!
+//! Spends are tallied in `BUCKETS` buckets of `window / BUCKETS` ledgers
+//! (rounded up) plus the current one, kept as a fixed ring. A spend so stops
+//! counting between `window` and `window` plus one bucket after it was made.
+
 use smart_wallet_interface::{types::SignerKey, PolicyInterface};
 use soroban_sdk::{
     auth::{Context, ContractContext},
-    contract, contracterror, contractimpl, panic_with_error, symbol_short, Address, Env,
-    TryFromVal, Vec,
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    Env, Symbol, TryFromVal, Val, Vec,
 };
 
 #[contracterror]
@@ -12,27 +22,251 @@ use soroban_sdk::{
 #[repr(u32)]
 pub enum Error {
     NotAllowed = 1,
+    NoLimit = 2,
+    LimitExceeded = 3,
+    DestinationNotAllowed = 4,
+    InvalidLimit = 5,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpendLimit {
+    pub max_amount: i128,
+    pub window: u32,                        // in ledgers
+    pub destinations: Option<Vec<Address>>, // None allows any destination
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spent {
+    pub width: u32,         // ledgers per bucket
+    pub bucket: u32,        // latest bucket, `ledger / width`
+    pub amounts: Vec<i128>, // `SLOTS` buckets, bucket `b` at `b % SLOTS`
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Limit(Address, Address, Symbol), // (wallet, token, fn_name)
+    Spent(Address, Address, Symbol), // ring of recent spends
+}
+
+pub const BUCKETS: u32 = 20;
+const SLOTS: u32 = BUCKETS + 1; // the full buckets of the window plus the current one
+
+const WEEK_OF_LEDGERS: u32 = 60 * 60 * 24 / 5 * 7;
+
+fn extend(env: &Env, key: &DataKey) {
+    let max_ttl = env.storage().max_ttl();
+
+    env.storage()
+        .persistent()
+        .extend_ttl(key, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+}
+
+fn get_limit(env: &Env, wallet: &Address, token: &Address, fn_name: &Symbol) -> Option<SpendLimit> {
+    env.storage().persistent().get(&DataKey::Limit(
+        wallet.clone(),
+        token.clone(),
+        fn_name.clone(),
+    ))
+}
+
+fn bucket_width(window: u32) -> u32 {
+    window.div_ceil(BUCKETS)
+}
+
+fn empty_ring(env: &Env) -> Vec<i128> {
+    let mut amounts = Vec::new(env);
+
+    for _ in 0..SLOTS {
+        amounts.push_back(0);
+    }
+
+    amounts
+}
+
+// The ring for `window`, moved up to the current ledger
+fn get_spent(env: &Env, wallet: &Address, token: &Address, fn_name: &Symbol, window: u32) -> Spent {
+    let width = bucket_width(window);
+    let stored: Option<Spent> = env.storage().persistent().get(&DataKey::Spent(
+        wallet.clone(),
+        token.clone(),
+        fn_name.clone(),
+    ));
+    let mut spent = stored.unwrap_or(Spent {
+        width,
+        bucket: env.ledger().sequence() / width,
+        amounts: empty_ring(env),
+    });
+    let bucket = env.ledger().sequence() / spent.width;
+
+    // Clear every slot the ring has moved past since the latest spend
+    for stale in 1..=bucket.saturating_sub(spent.bucket).min(SLOTS) {
+        spent.amounts.set((spent.bucket + stale) % SLOTS, 0);
+    }
+    spent.bucket = bucket;
+
+    // A new window gets a new ring, with what still counts in its current bucket
+    if spent.width != width {
+        let live = total(&spent);
+
+        spent = Spent {
+            width,
+            bucket: env.ledger().sequence() / width,
+            amounts: empty_ring(env),
+        };
+        spent.amounts.set(spent.bucket % SLOTS, live);
+    }
+
+    spent
+}
+
+fn total(spent: &Spent) -> i128 {
+    spent.amounts.iter().sum()
+}
+
+// (destination, amount) of the SEP-41 token functions a limit can cover
+fn destination_and_amount(env: &Env, fn_name: &Symbol, args: &Vec<Val>) -> (Option<Address>, i128) {
+    let (destination, amount) = if *fn_name == symbol_short!("transfer") {
+        (Some(1), 2) // transfer(from, to, amount)
+    } else if *fn_name == Symbol::new(env, "transfer_from") {
+        (Some(2), 3) // transfer_from(spender, from, to, amount)
+    } else if *fn_name == symbol_short!("approve") {
+        (Some(1), 2) // approve(from, spender, amount, expiration_ledger)
+    } else if *fn_name == symbol_short!("burn") {
+        (None, 1) // burn(from, amount)
+    } else if *fn_name == symbol_short!("burn_from") {
+        (None, 2) // burn_from(spender, from, amount)
+    } else {
+        panic_with_error!(env, Error::NotAllowed)
+    };
+
+    let destination = destination.map(|i| {
+        args.get(i)
+            .and_then(|v| Address::try_from_val(env, &v).ok())
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotAllowed))
+    });
+    let amount = args
+        .get(amount)
+        .and_then(|v| i128::try_from_val(env, &v).ok())
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotAllowed));
+
+    if amount < 0 {
+        panic_with_error!(env, Error::NotAllowed)
+    }
+
+    (destination, amount)
 }
 
 #[contract]
 pub struct Contract;
 
+#[contractimpl]
+impl Contract {
+    /// Sets `wallet`'s limit for `fn_name` on `token`. Spends already made in
+    /// the window keep counting against the new limit.
+    pub fn set_limit(
+        env: Env,
+        wallet: Address,
+        token: Address,
+        fn_name: Symbol,
+        limit: SpendLimit,
+    ) {
+        wallet.require_auth();
+
+        if limit.max_amount < 0 || limit.window == 0 {
+            panic_with_error!(&env, Error::InvalidLimit)
+        }
+
+        let key = DataKey::Limit(wallet, token, fn_name);
+
+        env.storage().persistent().set(&key, &limit);
+        extend(&env, &key);
+    }
+    pub fn remove_limit(env: Env, wallet: Address, token: Address, fn_name: Symbol) {
+        wallet.require_auth();
+
+        env.storage().persistent().remove(&DataKey::Limit(
+            wallet.clone(),
+            token.clone(),
+            fn_name.clone(),
+        ));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Spent(wallet, token, fn_name));
+    }
+    pub fn limit(env: Env, wallet: Address, token: Address, fn_name: Symbol) -> Option<SpendLimit> {
+        get_limit(&env, &wallet, &token, &fn_name)
+    }
+    /// Amount spent within the current window.
+    pub fn spent(env: Env, wallet: Address, token: Address, fn_name: Symbol) -> i128 {
+        match get_limit(&env, &wallet, &token, &fn_name) {
+            Some(limit) => total(&get_spent(&env, &wallet, &token, &fn_name, limit.window)),
+            None => 0,
+        }
+    }
+    /// What can still be spent right now.
+    pub fn available(env: Env, wallet: Address, token: Address, fn_name: Symbol) -> i128 {
+        match get_limit(&env, &wallet, &token, &fn_name) {
+            Some(limit) => {
+                let spent = total(&get_spent(&env, &wallet, &token, &fn_name, limit.window));
+
+                (limit.max_amount - spent).max(0)
+            }
+            None => 0,
+        }
+    }
+}
+
 #[contractimpl]
 impl PolicyInterface for Contract {
-    fn policy__(env: Env, _source: Address, _signer: SignerKey, contexts: Vec<Context>) {
+    fn policy__(env: Env, source: Address, _signer: SignerKey, contexts: Vec<Context>) {
+        // Only the wallet's own __check_auth may record spends against its limits
+        source.require_auth();
+
         for context in contexts.iter() {
-            match context {
-                Context::Contract(ContractContext { fn_name, args, .. }) => {
-                    if let Some(amount_val) = args.get(2) {
-                        if let Ok(amount) = i128::try_from_val(&env, &amount_val) {
-                            if fn_name == symbol_short!("transfer") && amount > 10_000_000 {
-                                panic_with_error!(&env, Error::NotAllowed)
-                            }
-                        }
-                    }
+            let Context::Contract(ContractContext {
+                contract,
+                fn_name,
+                args,
+            }) = context
+            else {
+                panic_with_error!(&env, Error::NotAllowed)
+            };
+
+            let limit = get_limit(&env, &source, &contract, &fn_name)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::NoLimit));
+            let (destination, amount) = destination_and_amount(&env, &fn_name, &args);
+
+            if let (Some(allowed), Some(destination)) = (&limit.destinations, &destination) {
+                if !allowed.contains(destination) {
+                    panic_with_error!(&env, Error::DestinationNotAllowed)
                 }
-                _ => panic_with_error!(&env, Error::NotAllowed),
             }
+
+            let mut spent = get_spent(&env, &source, &contract, &fn_name, limit.window);
+
+            if total(&spent) + amount > limit.max_amount {
+                panic_with_error!(&env, Error::LimitExceeded)
+            }
+
+            let slot = spent.bucket % SLOTS;
+
+            spent
+                .amounts
+                .set(slot, spent.amounts.get_unchecked(slot) + amount);
+
+            // A limit in use lives as long as its spends
+            let limit_key = DataKey::Limit(source.clone(), contract.clone(), fn_name.clone());
+            let key = DataKey::Spent(source.clone(), contract, fn_name);
+
+            env.storage().persistent().set(&key, &spent);
+            extend(&env, &key);
+            extend(&env, &limit_key);
         }
     }
 }
+
+#[path = "./tests/test.rs"]
+mod test;
@@ -0,0 +1,309 @@
+#![cfg(test)]
+
+use crate::{Contract, ContractClient, DataKey, Error, SpendLimit, Spent, BUCKETS};
+use smart_wallet_interface::types::SignerKey;
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    symbol_short,
+    testutils::{storage::Persistent as _, Address as _, Ledger},
+    vec, Address, BytesN, Env, IntoVal, Symbol, Vec,
+};
+
+struct Setup<'a> {
+    env: Env,
+    policy: ContractClient<'a>,
+    wallet: Address,
+    token: Address,
+    treasury: Address,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let policy = ContractClient::new(&env, &env.register(Contract, ()));
+    let wallet = Address::generate(&env);
+    let token = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    policy.set_limit(
+        &wallet,
+        &token,
+        &symbol_short!("transfer"),
+        &SpendLimit {
+            max_amount: 1_000,
+            window: 100,
+            destinations: Some(vec![&env, treasury.clone()]),
+        },
+    );
+
+    Setup {
+        env,
+        policy,
+        wallet,
+        token,
+        treasury,
+    }
+}
+
+impl Setup<'_> {
+    fn transfer(&self, to: &Address, amount: i128) -> Vec<Context> {
+        vec![
+            &self.env,
+            Context::Contract(ContractContext {
+                contract: self.token.clone(),
+                fn_name: symbol_short!("transfer"),
+                args: vec![
+                    &self.env,
+                    self.wallet.into_val(&self.env),
+                    to.into_val(&self.env),
+                    amount.into_val(&self.env),
+                ],
+            }),
+        ]
+    }
+
+    fn check(&self, contexts: &Vec<Context>) -> Result<(), Error> {
+        let signer = SignerKey::Ed25519(BytesN::from_array(&self.env, &[1; 32]));
+
+        match self.policy.try_policy__(&self.wallet, &signer, contexts) {
+            Ok(_) => Ok(()),
+            Err(Ok(error)) => Err(Error::try_from(error).unwrap()),
+            Err(Err(error)) => panic!("{error:?}"),
+        }
+    }
+
+    fn advance(&self, ledgers: u32) {
+        self.env
+            .ledger()
+            .with_mut(|li| li.sequence_number += ledgers);
+    }
+}
+
+#[test]
+fn test_rolling_window() {
+    let s = setup();
+    let fn_name = symbol_short!("transfer");
+
+    assert_eq!(s.check(&s.transfer(&s.treasury, 600)), Ok(()));
+
+    s.advance(50);
+
+    assert_eq!(s.check(&s.transfer(&s.treasury, 400)), Ok(()));
+    assert_eq!(s.policy.spent(&s.wallet, &s.token, &fn_name), 1_000);
+    assert_eq!(s.policy.available(&s.wallet, &s.token, &fn_name), 0);
+    assert_eq!(
+        s.check(&s.transfer(&s.treasury, 1)),
+        Err(Error::LimitExceeded)
+    );
+
+    // The first spend leaves the window 100 ledgers after it was made, give
+    // or take the rest of its bucket of 100 / BUCKETS ledgers
+    s.advance(50);
+
+    assert_eq!(s.policy.available(&s.wallet, &s.token, &fn_name), 0);
+
+    s.advance(100 / BUCKETS);
+
+    assert_eq!(s.policy.available(&s.wallet, &s.token, &fn_name), 600);
+    assert_eq!(s.check(&s.transfer(&s.treasury, 600)), Ok(()));
+    assert_eq!(
+        s.check(&s.transfer(&s.treasury, 1)),
+        Err(Error::LimitExceeded)
+    );
+}
+
+#[test]
+fn test_same_ledger_spends_add_up() {
+    let s = setup();
+
+    assert_eq!(s.check(&s.transfer(&s.treasury, 500)), Ok(()));
+    assert_eq!(s.check(&s.transfer(&s.treasury, 500)), Ok(()));
+    assert_eq!(
+        s.check(&s.transfer(&s.treasury, 1)),
+        Err(Error::LimitExceeded)
+    );
+    assert_eq!(
+        s.policy
+            .spent(&s.wallet, &s.token, &symbol_short!("transfer")),
+        1_000
+    );
+}
+
+#[test]
+fn test_destination_allowlist() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+
+    assert_eq!(
+        s.check(&s.transfer(&stranger, 1)),
+        Err(Error::DestinationNotAllowed)
+    );
+
+    // Without an allowlist any destination is fine
+    s.policy.set_limit(
+        &s.wallet,
+        &s.token,
+        &symbol_short!("transfer"),
+        &SpendLimit {
+            max_amount: 1_000,
+            window: 100,
+            destinations: None,
+        },
+    );
+
+    assert_eq!(s.check(&s.transfer(&stranger, 1)), Ok(()));
+}
+
+#[test]
+fn test_unconfigured_contexts_are_refused() {
+    let s = setup();
+    let approve = vec![
+        &s.env,
+        Context::Contract(ContractContext {
+            contract: s.token.clone(),
+            fn_name: symbol_short!("approve"),
+            args: vec![
+                &s.env,
+                s.wallet.into_val(&s.env),
+                s.treasury.into_val(&s.env),
+                1i128.into_val(&s.env),
+                0u32.into_val(&s.env),
+            ],
+        }),
+    ];
+
+    assert_eq!(s.check(&approve), Err(Error::NoLimit));
+
+    // Another wallet's limits don't carry over
+    let other = Address::generate(&s.env);
+    let signer = SignerKey::Ed25519(BytesN::from_array(&s.env, &[1; 32]));
+
+    assert!(s
+        .policy
+        .try_policy__(&other, &signer, &s.transfer(&s.treasury, 1))
+        .is_err());
+
+    // Limited functions must be ones whose amount the policy understands
+    s.policy.set_limit(
+        &s.wallet,
+        &s.token,
+        &Symbol::new(&s.env, "set_admin"),
+        &SpendLimit {
+            max_amount: 1,
+            window: 1,
+            destinations: None,
+        },
+    );
+
+    let set_admin = vec![
+        &s.env,
+        Context::Contract(ContractContext {
+            contract: s.token.clone(),
+            fn_name: Symbol::new(&s.env, "set_admin"),
+            args: vec![&s.env, s.treasury.into_val(&s.env)],
+        }),
+    ];
+
+    assert_eq!(s.check(&set_admin), Err(Error::NotAllowed));
+}
+
+#[test]
+fn test_invalid_limit() {
+    let s = setup();
+
+    assert_eq!(
+        s.policy.try_set_limit(
+            &s.wallet,
+            &s.token,
+            &symbol_short!("transfer"),
+            &SpendLimit {
+                max_amount: 1,
+                window: 0,
+                destinations: None,
+            },
+        ),
+        Err(Ok(Error::InvalidLimit.into()))
+    );
+
+    s.policy
+        .remove_limit(&s.wallet, &s.token, &symbol_short!("transfer"));
+
+    assert_eq!(
+        s.policy
+            .limit(&s.wallet, &s.token, &symbol_short!("transfer")),
+        None
+    );
+    assert_eq!(s.check(&s.transfer(&s.treasury, 1)), Err(Error::NoLimit));
+}
+
+#[test]
+fn test_spends_use_a_fixed_ring() {
+    let s = setup();
+    let fn_name = symbol_short!("transfer");
+    let ring = || -> Spent {
+        s.env.as_contract(&s.policy.address, || {
+            s.env
+                .storage()
+                .persistent()
+                .get(&DataKey::Spent(
+                    s.wallet.clone(),
+                    s.token.clone(),
+                    fn_name.clone(),
+                ))
+                .unwrap()
+        })
+    };
+
+    // A spend every ledger for a while never grows the ring
+    for _ in 0..250 {
+        assert_eq!(s.check(&s.transfer(&s.treasury, 1)), Ok(()));
+        s.advance(1);
+    }
+
+    assert_eq!(ring().amounts.len(), BUCKETS + 1);
+    assert_eq!(s.policy.spent(&s.wallet, &s.token, &fn_name), 100);
+
+    // A new window starts over with what still counts
+    s.policy.set_limit(
+        &s.wallet,
+        &s.token,
+        &fn_name,
+        &SpendLimit {
+            max_amount: 1_000,
+            window: 1_000,
+            destinations: None,
+        },
+    );
+
+    assert_eq!(s.policy.spent(&s.wallet, &s.token, &fn_name), 100);
+    assert_eq!(s.check(&s.transfer(&s.treasury, 900)), Ok(()));
+    assert_eq!(
+        s.check(&s.transfer(&s.treasury, 1)),
+        Err(Error::LimitExceeded)
+    );
+    assert_eq!(ring().width, 1_000 / BUCKETS);
+}
+
+#[test]
+fn test_limit_in_use_stays_live() {
+    let s = setup();
+    let key = DataKey::Limit(s.wallet.clone(), s.token.clone(), symbol_short!("transfer"));
+    let ttl = || {
+        s.env.as_contract(&s.policy.address, || {
+            s.env.storage().persistent().get_ttl(&key)
+        })
+    };
+    let max_ttl = ttl();
+
+    // keep the policy contract itself around
+    s.env.as_contract(&s.policy.address, || {
+        s.env.storage().instance().extend_ttl(max_ttl, max_ttl)
+    });
+    s.advance(60 * 60 * 24 / 5 * 7 + 1);
+
+    assert!(ttl() < max_ttl);
+    assert_eq!(s.check(&s.transfer(&s.treasury, 1)), Ok(()));
+    assert_eq!(ttl(), max_ttl);
+}
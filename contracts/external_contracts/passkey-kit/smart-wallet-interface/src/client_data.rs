@@ -0,0 +1,154 @@
+use soroban_sdk::Bytes;
+
+/// The clientDataJSON members checked during verification, as raw JSON string contents.
+pub struct ClientData {
+    pub challenge: Option<Bytes>,
+    pub type_: Option<Bytes>,
+    pub origin: Option<Bytes>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Challenge,
+    Type,
+    Origin,
+    Other,
+}
+
+// How much of the JSON is copied out of the host at a time
+const CHUNK_LEN: u32 = 64;
+
+/// Single pass over clientDataJSON of any length using a fixed-size window.
+/// Only top-level string members are picked out and nested values are skipped.
+/// Escape sequences are kept as is, so an escaped challenge, type or origin
+/// simply fails to match. Returns `None` for anything that isn't one object,
+/// or that repeats a member we read.
+pub fn parse(client_data_json: &Bytes) -> Option<ClientData> {
+    let len = client_data_json.len();
+    let mut chunk = [0u8; CHUNK_LEN as usize];
+    let mut values: [Option<(u32, u32)>; 3] = [None; 3];
+
+    let mut depth = 0u32;
+    let mut closed = false;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0u32;
+    let mut expect_key = false;
+    let mut key = Field::Other;
+    let mut offset = 0u32;
+
+    while offset < len {
+        let end = (offset + CHUNK_LEN).min(len);
+        let window = &mut chunk[..(end - offset) as usize];
+
+        client_data_json.slice(offset..end).copy_into_slice(window);
+
+        for (i, &byte) in window.iter().enumerate() {
+            let pos = offset + i as u32;
+
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+
+                    if depth == 1 {
+                        if expect_key {
+                            key = field(client_data_json, start, pos);
+                            expect_key = false;
+                        } else if key != Field::Other {
+                            let value = &mut values[key as usize];
+
+                            if value.is_some() {
+                                return None;
+                            }
+
+                            *value = Some((start, pos));
+                            key = Field::Other;
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            if closed {
+                if !is_whitespace(byte) {
+                    return None;
+                }
+
+                continue;
+            }
+
+            match byte {
+                b'"' if depth > 0 => {
+                    in_string = true;
+                    start = pos + 1;
+                }
+                b'{' => {
+                    depth += 1;
+
+                    if depth == 1 {
+                        expect_key = true;
+                    }
+                }
+                b'[' if depth > 0 => depth += 1,
+                b'}' | b']' if depth > 0 => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        closed = true;
+                    }
+                }
+                b',' if depth == 1 => {
+                    expect_key = true;
+                    key = Field::Other;
+                }
+                _ if depth == 0 && !is_whitespace(byte) => return None,
+                _ => {}
+            }
+        }
+
+        offset = end;
+    }
+
+    if !closed {
+        return None;
+    }
+
+    let value = |field: Field| {
+        values[field as usize].map(|(start, end)| client_data_json.slice(start..end))
+    };
+
+    Some(ClientData {
+        challenge: value(Field::Challenge),
+        type_: value(Field::Type),
+        origin: value(Field::Origin),
+    })
+}
+
+fn field(client_data_json: &Bytes, start: u32, end: u32) -> Field {
+    let mut name = [0u8; 9];
+    let name_len = end - start;
+
+    if name_len > name.len() as u32 {
+        return Field::Other;
+    }
+
+    let name = &mut name[..name_len as usize];
+
+    client_data_json.slice(start..end).copy_into_slice(name);
+
+    match &*name {
+        b"challenge" => Field::Challenge,
+        b"type" => Field::Type,
+        b"origin" => Field::Origin,
+        _ => Field::Other,
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
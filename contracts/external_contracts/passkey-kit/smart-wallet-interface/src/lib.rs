@@ -3,15 +3,23 @@
 use soroban_sdk::{auth::Context, contractclient, Address, BytesN, Env, Vec};
 use types::{Signer, SignerKey};
 
+pub mod base64_url;
+pub mod client_data;
+pub mod secp256k1;
 pub mod types;
+pub mod upgrade;
 
+/// Signer and upgrade management. Constructors are left to each wallet, as
+/// their deploy arguments differ.
 #[contractclient(name = "SmartWalletClient")]
 pub trait SmartWalletInterface {
-    fn __constructor(env: Env, signer: Signer);
     fn add_signer(env: Env, signer: Signer);
     fn update_signer(env: Env, signer: Signer);
     fn remove_signer(env: Env, signer_key: SignerKey);
+    fn schedule_upgrade(env: Env, hash: BytesN<32>, earliest_ledger: u32);
     fn update_contract_code(env: Env, hash: BytesN<32>);
+    fn cancel_upgrade(env: Env);
+    fn cancel_removal(env: Env, signer_key: SignerKey);
 }
 
 #[contractclient(name = "PolicyClient")]
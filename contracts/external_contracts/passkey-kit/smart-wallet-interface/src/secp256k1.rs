@@ -0,0 +1,75 @@
+//! Ethereum-style secp256k1 signers, shared by the wallets built on this
+//! interface. Keys are a 65-byte uncompressed public key or a 20-byte address,
+//! and sign the payload the way `personal_sign` does.
+
+use soroban_sdk::{contracterror, crypto::Hash, panic_with_error, Bytes, Env};
+
+use crate::types::Secp256k1Signature;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum Secp256k1Error {
+    InvalidKey = 40,
+    SignatureMismatch = 41,
+}
+
+// EIP-191 `personal_sign` prefix for a 32-byte message
+const ETH_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+
+/// A secp256k1 key is either a 65-byte uncompressed public key or a 20-byte Ethereum address.
+pub fn verify_key(env: &Env, key: &Bytes) {
+    let valid = match key.len() {
+        65 => key.get_unchecked(0) == 0x04,
+        20 => true,
+        _ => false,
+    };
+
+    if !valid {
+        panic_with_error!(env, Secp256k1Error::InvalidKey)
+    }
+}
+
+/// Recovers the signer of `keccak256(prefix || signature_payload)` and checks
+/// it against `key`, comparing addresses when `key` is 20 bytes.
+pub fn verify_signature(
+    env: &Env,
+    signature_payload: &Hash<32>,
+    key: &Bytes,
+    signature: Secp256k1Signature,
+) {
+    let Secp256k1Signature {
+        signature,
+        recovery_id,
+    } = signature;
+
+    let mut message = Bytes::from_slice(env, ETH_MESSAGE_PREFIX);
+
+    message.extend_from_array(&signature_payload.to_array());
+
+    let digest = env.crypto().keccak256(&message);
+    // Accept both raw (0/1) and Ethereum-style (27/28) recovery ids
+    let recovery_id = if recovery_id >= 27 {
+        recovery_id - 27
+    } else {
+        recovery_id
+    };
+    let public_key = env
+        .crypto()
+        .secp256k1_recover(&digest, &signature, recovery_id);
+
+    let recovered = if key.len() == 20 {
+        // The address is the last 20 bytes of keccak256(x || y)
+        let hash = env
+            .crypto()
+            .keccak256(&Bytes::from_slice(env, &public_key.to_array()[1..]));
+
+        Bytes::from_slice(env, &hash.to_array()[12..])
+    } else {
+        Bytes::from(public_key)
+    };
+
+    if recovered != *key {
+        panic_with_error!(env, Secp256k1Error::SignatureMismatch)
+    }
+}
@@ -0,0 +1,240 @@
+//! Timelocked code upgrades, shared by the wallets built on this interface.
+//!
+//! Unlimited signers are the ones that can cancel an upgrade, so any change to
+//! one beyond extending its expiration, be it removing, limiting or expiring
+//! it or swapping its key, waits out the same delay. Until then it can still
+//! cancel both.
+
+use soroban_sdk::{
+    contracterror, contracttype, panic_with_error, symbol_short, BytesN, Env, Symbol,
+};
+
+use crate::types::{Signer, SignerExpiration, SignerKey, SignerStorage, SignerVal};
+
+// Roughly a day of ledgers: the window every signer has to notice and cancel
+pub const UPGRADE_DELAY: u32 = 60 * 60 * 24 / 5;
+
+const EVENT_TAG: Symbol = symbol_short!("sw_v1");
+const PENDING_UPGRADE: Symbol = symbol_short!("upgrade");
+const PENDING_REMOVAL: Symbol = symbol_short!("removal");
+const WEEK_OF_LEDGERS: u32 = 60 * 60 * 24 / 5 * 7;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum UpgradeError {
+    NoUpgrade = 30,
+    TooEarly = 31,
+    HashMismatch = 32,
+    NoRemoval = 33,
+    RemovalMismatch = 34,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingUpgrade {
+    pub hash: BytesN<32>,
+    pub earliest_ledger: u32,
+}
+
+/// What happens to a signer that guards upgrades once its timelock passes.
+/// Not exported, as it holds a `Signer`: importers bring both from here.
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignerChange {
+    Remove,
+    Update(Signer),
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingRemoval {
+    pub earliest_ledger: u32,
+    pub change: SignerChange,
+}
+
+fn extend_instance(env: &Env) {
+    let max_ttl = env.storage().max_ttl();
+
+    env.storage()
+        .instance()
+        .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+}
+
+pub fn get_pending(env: &Env) -> Option<PendingUpgrade> {
+    env.storage().instance().get(&PENDING_UPGRADE)
+}
+
+pub fn schedule(env: &Env, hash: BytesN<32>, earliest_ledger: u32) {
+    if earliest_ledger < env.ledger().sequence() + UPGRADE_DELAY {
+        panic_with_error!(env, UpgradeError::TooEarly)
+    }
+
+    let upgrade = PendingUpgrade {
+        hash,
+        earliest_ledger,
+    };
+
+    // Scheduling again replaces the previous upgrade
+    env.storage().instance().set(&PENDING_UPGRADE, &upgrade);
+    extend_instance(env);
+
+    env.events().publish(
+        (EVENT_TAG, PENDING_UPGRADE, symbol_short!("schedule")),
+        upgrade,
+    );
+}
+
+pub fn execute(env: &Env, hash: BytesN<32>) {
+    let upgrade =
+        get_pending(env).unwrap_or_else(|| panic_with_error!(env, UpgradeError::NoUpgrade));
+
+    if upgrade.hash != hash {
+        panic_with_error!(env, UpgradeError::HashMismatch)
+    }
+    if env.ledger().sequence() < upgrade.earliest_ledger {
+        panic_with_error!(env, UpgradeError::TooEarly)
+    }
+
+    env.storage().instance().remove(&PENDING_UPGRADE);
+    extend_instance(env);
+
+    env.events().publish(
+        (EVENT_TAG, PENDING_UPGRADE, symbol_short!("execute")),
+        upgrade,
+    );
+
+    env.deployer().update_current_contract_wasm(hash);
+}
+
+pub fn cancel(env: &Env) {
+    let upgrade =
+        get_pending(env).unwrap_or_else(|| panic_with_error!(env, UpgradeError::NoUpgrade));
+
+    env.storage().instance().remove(&PENDING_UPGRADE);
+    extend_instance(env);
+
+    env.events().publish(
+        (EVENT_TAG, PENDING_UPGRADE, symbol_short!("cancel")),
+        upgrade,
+    );
+}
+
+/// Can this signer cancel upgrades for at least the whole delay, i.e. is it
+/// unlimited and not expiring before then?
+pub fn guards_upgrades(env: &Env, signer_val: &SignerVal) -> bool {
+    let (signer_expiration, signer_limits) = match signer_val {
+        SignerVal::Policy(signer_expiration, signer_limits)
+        | SignerVal::Ed25519(signer_expiration, signer_limits)
+        | SignerVal::Secp256r1(_, signer_expiration, signer_limits)
+        | SignerVal::Secp256k1(signer_expiration, signer_limits) => {
+            (signer_expiration, signer_limits)
+        }
+    };
+
+    signer_limits
+        .0
+        .as_ref()
+        .is_none_or(|limits| limits.is_empty())
+        && signer_expiration
+            .0
+            .is_none_or(|ledger| ledger >= env.ledger().sequence() + UPGRADE_DELAY)
+}
+
+/// Does replacing `previous_val` with `next` (or removing it, for `None`)
+/// have to wait for `removal_due`? Only extending the expiration of a signer
+/// that guards upgrades, keeping everything else, doesn't.
+pub fn change_is_timelocked(
+    env: &Env,
+    previous_val: &SignerVal,
+    previous_storage: &SignerStorage,
+    next: Option<(&SignerVal, &SignerStorage)>,
+) -> bool {
+    guards_upgrades(env, previous_val)
+        && !next.is_some_and(|(signer_val, signer_storage)| {
+            signer_storage == previous_storage && only_extends(previous_val, signer_val)
+        })
+}
+
+fn only_extends(previous_val: &SignerVal, signer_val: &SignerVal) -> bool {
+    let later = |previous: &SignerExpiration, next: &SignerExpiration| match (previous.0, next.0) {
+        (_, None) => true,
+        (Some(previous), Some(next)) => next >= previous,
+        (None, Some(_)) => false,
+    };
+
+    match (previous_val, signer_val) {
+        (SignerVal::Policy(e0, l0), SignerVal::Policy(e1, l1))
+        | (SignerVal::Ed25519(e0, l0), SignerVal::Ed25519(e1, l1))
+        | (SignerVal::Secp256k1(e0, l0), SignerVal::Secp256k1(e1, l1)) => l0 == l1 && later(e0, e1),
+        (SignerVal::Secp256r1(k0, e0, l0), SignerVal::Secp256r1(k1, e1, l1)) => {
+            k0 == k1 && l0 == l1 && later(e0, e1)
+        }
+        _ => false,
+    }
+}
+
+pub fn get_pending_removal(env: &Env, signer_key: &SignerKey) -> Option<PendingRemoval> {
+    env.storage()
+        .persistent()
+        .get(&(PENDING_REMOVAL, signer_key.clone()))
+}
+
+/// Gate for a timelocked change to a signer. The first call schedules the change, emits it and returns
+/// `false`, leaving the signer as it is. Once `UPGRADE_DELAY` has passed the
+/// same change returns `true` and the caller goes ahead. A different change
+/// fails until the scheduled one is cancelled.
+pub fn removal_due(env: &Env, signer_key: &SignerKey, change: &SignerChange) -> bool {
+    let key = (PENDING_REMOVAL, signer_key.clone());
+
+    match get_pending_removal(env, signer_key) {
+        Some(pending) => {
+            if pending.change != *change {
+                panic_with_error!(env, UpgradeError::RemovalMismatch)
+            }
+            if env.ledger().sequence() < pending.earliest_ledger {
+                panic_with_error!(env, UpgradeError::TooEarly)
+            }
+
+            env.storage().persistent().remove(&key);
+
+            true
+        }
+        None => {
+            let max_ttl = env.storage().max_ttl();
+            let pending = PendingRemoval {
+                earliest_ledger: env.ledger().sequence() + UPGRADE_DELAY,
+                change: change.clone(),
+            };
+
+            env.storage().persistent().set(&key, &pending);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+            extend_instance(env);
+
+            env.events().publish(
+                (EVENT_TAG, PENDING_REMOVAL, symbol_short!("schedule")),
+                (signer_key.clone(), pending),
+            );
+
+            false
+        }
+    }
+}
+
+pub fn cancel_removal(env: &Env, signer_key: &SignerKey) {
+    if get_pending_removal(env, signer_key).is_none() {
+        panic_with_error!(env, UpgradeError::NoRemoval)
+    }
+
+    env.storage()
+        .persistent()
+        .remove(&(PENDING_REMOVAL, signer_key.clone()));
+    extend_instance(env);
+
+    env.events().publish(
+        (EVENT_TAG, PENDING_REMOVAL, symbol_short!("cancel")),
+        signer_key.clone(),
+    );
+}
@@ -1,7 +1,7 @@
 use smart_wallet_interface::types::{Signatures, SignerKey, SignerLimits};
 use soroban_sdk::{
     auth::{Context, ContractContext},
-    Env, FromVal, Symbol,
+    Env, FromVal, Symbol, Vec,
 };
 
 use crate::signer::verify_signer_limit_keys;
@@ -13,11 +13,29 @@ pub fn verify_context(
     signer_limits: &SignerLimits,
     signatures: &Signatures,
 ) -> bool {
+    match context_limit_keys(env, context, signer_key, signer_limits) {
+        None => false, // signer limitations not met
+        Some(signer_limits_keys) => {
+            verify_signer_limit_keys(env, signer_key, signatures, &signer_limits_keys, context);
+
+            true
+        }
+    }
+}
+
+/// The keys `signer_key` needs alongside it for `context`, or `None` if its
+/// limits don't reach `context` at all.
+pub fn context_limit_keys(
+    env: &Env,
+    context: &Context,
+    signer_key: &SignerKey,
+    signer_limits: &SignerLimits,
+) -> Option<Option<Vec<SignerKey>>> {
     match &signer_limits.0 {
-        None => true, // Signer has no limits, it can do anything
+        None => Some(None), // Signer has no limits, it can do anything
         Some(signer_limits) => {
             if signer_limits.is_empty() {
-                return true;
+                return Some(None);
             }
 
             match context {
@@ -26,66 +44,24 @@ pub fn verify_context(
                     fn_name,
                     args,
                 }) => {
-                    match signer_limits.get(contract.clone()) {
-                        None => false, // signer limitations not met
-                        Some(signer_limits_keys) => {
-                            // If this signer has a smart wallet context limit, limit that context to only removing itself
-                            if *contract == env.current_contract_address()
-                                && *fn_name != Symbol::new(&env, "remove_signer")
-                                || (*fn_name == Symbol::new(&env, "remove_signer")
-                                    && SignerKey::from_val(env, &args.get_unchecked(0))
-                                        != *signer_key)
-                            {
-                                return false; // self trying to do something other than remove itself
-                            }
-
-                            verify_signer_limit_keys(
-                                env,
-                                signer_key,
-                                signatures,
-                                &signer_limits_keys,
-                                &context,
-                            );
+                    let signer_limits_keys = signer_limits.get(contract.clone())?;
 
-                            true
-                        }
+                    // If this signer has a smart wallet context limit, limit that context to only removing itself
+                    if *contract == env.current_contract_address()
+                        && *fn_name != Symbol::new(&env, "remove_signer")
+                        || (*fn_name == Symbol::new(&env, "remove_signer")
+                            && SignerKey::from_val(env, &args.get_unchecked(0)) != *signer_key)
+                    {
+                        return None; // self trying to do something other than remove itself
                     }
-                }
-                Context::CreateContractHostFn(_) => {
-                    // Only signers with the smart wallet context signer limit can deploy contracts
-                    match signer_limits.get(env.current_contract_address()) {
-                        None => false, // signer limitations not met
-                        Some(signer_limits_keys) => {
-                            verify_signer_limit_keys(
-                                env,
-                                signer_key,
-                                signatures,
-                                &signer_limits_keys,
-                                &context,
-                            );
 
-                            true
-                        }
-                    }
+                    Some(signer_limits_keys)
                 }
-                Context::CreateContractWithCtorHostFn(_) => {
-                    // Only signers with the smart wallet context signer limit can deploy contracts
-                    match signer_limits.get(env.current_contract_address()) {
-                        None => false, // signer limitations not met
-                        Some(signer_limits_keys) => {
-                            verify_signer_limit_keys(
-                                env,
-                                signer_key,
-                                signatures,
-                                &signer_limits_keys,
-                                &context,
-                            );
-
-                            true
-                        }
-                    }
+                // Only signers with the smart wallet context signer limit can deploy contracts
+                Context::CreateContractHostFn(_) | Context::CreateContractWithCtorHostFn(_) => {
+                    signer_limits.get(env.current_contract_address())
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
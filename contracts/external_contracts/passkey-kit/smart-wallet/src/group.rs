@@ -0,0 +1,112 @@
+use smart_wallet_interface::types::{Signatures, SignerKey, SignerVal};
+use soroban_sdk::{
+    auth::Context, contracterror, contracttype, panic_with_error, symbol_short, Env, Symbol, Vec,
+};
+
+use crate::{
+    context::context_limit_keys, signer::get_signer_val_storage, storage::extend_group, EVENT_TAG,
+};
+
+pub const GROUP: Symbol = symbol_short!("group");
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum GroupError {
+    InvalidGroup = 20,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignerGroup {
+    pub members: Vec<SignerKey>,
+    pub threshold: u32,
+}
+
+pub fn get_group(env: &Env, name: &Symbol) -> Option<SignerGroup> {
+    env.storage().persistent().get(&(GROUP, name.clone()))
+}
+
+pub fn set_group(env: &Env, name: Symbol, members: Vec<SignerKey>, threshold: u32) {
+    let mut unique = Vec::new(env);
+
+    for member in members.iter() {
+        // No nested groups, and each member counts once
+        if matches!(member, SignerKey::Group(_)) || unique.contains(&member) {
+            panic_with_error!(env, GroupError::InvalidGroup)
+        }
+        unique.push_back(member);
+    }
+
+    if threshold == 0 || threshold > members.len() {
+        panic_with_error!(env, GroupError::InvalidGroup)
+    }
+
+    let group = SignerGroup { members, threshold };
+
+    env.storage()
+        .persistent()
+        .set(&(GROUP, name.clone()), &group);
+    extend_group(env, &name);
+
+    env.events().publish((EVENT_TAG, GROUP, name), group);
+}
+
+pub fn remove_group(env: &Env, name: Symbol) {
+    if get_group(env, &name).is_none() {
+        panic_with_error!(env, GroupError::InvalidGroup)
+    }
+
+    env.storage().persistent().remove(&(GROUP, name.clone()));
+
+    env.events()
+        .publish((EVENT_TAG, symbol_short!("ungroup"), name), ());
+}
+
+/// Have at least `threshold` members of group `name` signed for `context`?
+/// A member only counts while it is unexpired and its own limits reach
+/// `context`. Its limit keys aren't checked here, as they may well name this
+/// group again; they apply when the member authorizes on its own.
+pub fn group_threshold_met(
+    env: &Env,
+    name: &Symbol,
+    signatures: &Signatures,
+    context: &Context,
+) -> bool {
+    match get_group(env, name) {
+        None => false,
+        Some(group) => {
+            extend_group(env, name);
+
+            let signed = group
+                .members
+                .iter()
+                .filter(|member| {
+                    signatures.0.contains_key(member.clone())
+                        && member_can_sign(env, member, context)
+                })
+                .count() as u32;
+
+            signed >= group.threshold
+        }
+    }
+}
+
+fn member_can_sign(env: &Env, member: &SignerKey, context: &Context) -> bool {
+    let Some((signer_val, _)) = get_signer_val_storage(env, member, false) else {
+        return false;
+    };
+    let (signer_expiration, signer_limits) = match signer_val {
+        SignerVal::Policy(signer_expiration, signer_limits)
+        | SignerVal::Ed25519(signer_expiration, signer_limits)
+        | SignerVal::Secp256r1(_, signer_expiration, signer_limits)
+        | SignerVal::Secp256k1(signer_expiration, signer_limits) => {
+            (signer_expiration, signer_limits)
+        }
+    };
+    let expired = signer_expiration
+        .0
+        .is_some_and(|ledger| env.ledger().sequence() > ledger);
+
+    !expired && context_limit_keys(env, context, member, &signer_limits).is_some()
+}
@@ -1,22 +1,29 @@
 #![no_std]
 
 use context::verify_context;
-use signer::{get_signer_val_storage, process_signer, store_signer, verify_signer_expiration};
+use signer::{
+    get_signer_val_storage, process_signer, store_signer, unindex_signer, verify_signer_expiration,
+};
 use smart_wallet_interface::{
+    secp256k1,
     types::{Error, Signature, Signatures, Signer, SignerKey, SignerStorage, SignerVal},
+    upgrade::{self, PendingRemoval, PendingUpgrade, SignerChange},
     PolicyClient, SmartWalletInterface,
 };
 use soroban_sdk::{
     auth::{Context, CustomAccountInterface},
     contract, contractimpl,
     crypto::Hash,
-    panic_with_error, symbol_short, BytesN, Env, Symbol, Vec,
+    panic_with_error, symbol_short, Address, Bytes, BytesN, Env, Symbol, Val, Vec,
 };
+use group::SignerGroup;
+use recovery::{Recovery, RecoveryConfig};
 use storage::extend_instance;
 use verify::verify_secp256r1_signature;
 
-mod base64_url;
 mod context;
+mod group;
+mod recovery;
 mod signer;
 mod storage;
 mod types;
@@ -24,8 +31,22 @@ mod verify;
 
 #[path = "./tests/test.rs"]
 mod test;
+#[path = "./tests/test_client_data.rs"]
+mod test_client_data;
+#[path = "./tests/test_execute.rs"]
+mod test_execute;
 #[path = "./tests/test_extra.rs"]
 mod test_extra;
+#[path = "./tests/test_group.rs"]
+mod test_group;
+#[path = "./tests/test_recovery.rs"]
+mod test_recovery;
+#[path = "./tests/test_secp256k1.rs"]
+mod test_secp256k1;
+#[path = "./tests/test_signers.rs"]
+mod test_signers;
+#[path = "./tests/test_upgrade.rs"]
+mod test_upgrade;
 
 #[contract]
 pub struct Contract;
@@ -34,10 +55,14 @@ const EVENT_TAG: Symbol = symbol_short!("sw_v1");
 const INITIALIZED: Symbol = symbol_short!("init");
 
 #[contractimpl]
-impl SmartWalletInterface for Contract {
-    fn __constructor(env: Env, signer: Signer) {
+impl Contract {
+    pub fn __constructor(env: Env, signer: Signer) {
         Self::add_signer(env, signer);
     }
+}
+
+#[contractimpl]
+impl SmartWalletInterface for Contract {
     fn add_signer(env: Env, signer: Signer) {
         if env
             .storage()
@@ -66,7 +91,23 @@ impl SmartWalletInterface for Contract {
     fn update_signer(env: Env, signer: Signer) {
         env.current_contract_address().require_auth();
 
-        let (signer_key, signer_val, signer_storage) = process_signer(signer);
+        let (signer_key, signer_val, signer_storage) = process_signer(signer.clone());
+
+        // Changing an unlimited signer beyond extending it is timelocked too,
+        // `removal_due` emits the schedule
+        if let Some((previous_signer_val, previous_signer_storage)) =
+            get_signer_val_storage(&env, &signer_key, false)
+        {
+            if upgrade::change_is_timelocked(
+                &env,
+                &previous_signer_val,
+                &previous_signer_storage,
+                Some((&signer_val, &signer_storage)),
+            ) && !upgrade::removal_due(&env, &signer_key, &SignerChange::Update(signer))
+            {
+                return;
+            }
+        }
 
         store_signer(&env, &signer_key, &signer_val, &signer_storage, true);
 
@@ -81,29 +122,170 @@ impl SmartWalletInterface for Contract {
         env.current_contract_address().require_auth();
 
         match get_signer_val_storage(&env, &signer_key, false) {
-            Some((_, signer_storage)) => match signer_storage {
-                SignerStorage::Persistent => {
-                    env.storage().persistent().remove::<SignerKey>(&signer_key);
+            Some((signer_val, signer_storage)) => {
+                // Signers that can cancel upgrades only leave once the timelock has passed
+                if upgrade::change_is_timelocked(&env, &signer_val, &signer_storage, None)
+                    && !upgrade::removal_due(&env, &signer_key, &SignerChange::Remove)
+                {
+                    return;
                 }
-                SignerStorage::Temporary => {
-                    env.storage().temporary().remove::<SignerKey>(&signer_key);
+
+                match signer_storage {
+                    SignerStorage::Persistent => {
+                        env.storage().persistent().remove::<SignerKey>(&signer_key);
+                    }
+                    SignerStorage::Temporary => {
+                        env.storage().temporary().remove::<SignerKey>(&signer_key);
+                    }
                 }
-            },
+            }
             None => panic_with_error!(env, Error::NotFound),
         }
 
+        unindex_signer(&env, &signer_key);
+
         extend_instance(&env);
 
         env.events()
             .publish((EVENT_TAG, symbol_short!("remove"), signer_key), ());
     }
+    fn schedule_upgrade(env: Env, hash: BytesN<32>, earliest_ledger: u32) {
+        env.current_contract_address().require_auth();
+
+        upgrade::schedule(&env, hash, earliest_ledger);
+    }
     fn update_contract_code(env: Env, hash: BytesN<32>) {
         env.current_contract_address().require_auth();
 
-        env.deployer().update_current_contract_wasm(hash);
+        // Only a scheduled upgrade, and only once its timelock has passed
+        upgrade::execute(&env, hash);
+    }
+    fn cancel_upgrade(env: Env) {
+        // Any unlimited signer can cancel; limited ones never reach the wallet's own contexts
+        env.current_contract_address().require_auth();
+
+        upgrade::cancel(&env);
+    }
+    fn cancel_removal(env: Env, signer_key: SignerKey) {
+        env.current_contract_address().require_auth();
+
+        upgrade::cancel_removal(&env, &signer_key);
+    }
+}
+
+// Threshold groups. A `SignerKey::Group(name)` in a signer's limits is met once
+// `threshold` of the group's members have signed.
+#[contractimpl]
+impl Contract {
+    pub fn set_group(env: Env, name: Symbol, members: Vec<SignerKey>, threshold: u32) {
+        env.current_contract_address().require_auth();
+
+        group::set_group(&env, name, members, threshold);
+
+        extend_instance(&env);
+    }
+    pub fn remove_group(env: Env, name: Symbol) {
+        env.current_contract_address().require_auth();
+
+        group::remove_group(&env, name);
 
         extend_instance(&env);
     }
+    pub fn group(env: Env, name: Symbol) -> Option<SignerGroup> {
+        group::get_group(&env, &name)
+    }
+}
+
+#[contractimpl]
+impl Contract {
+    /// Every live signer with its storage class, expiration and limits, in the order added.
+    pub fn list_signers(env: Env) -> Vec<Signer> {
+        signer::list_signers(&env)
+    }
+}
+
+// Batched calls. Each call is made by the wallet itself, so callees see the
+// wallet as a direct invoker and need no further signatures. Only unlimited
+// signers can authorize it, since limited ones never reach the wallet's own
+// contexts.
+#[contractimpl]
+impl Contract {
+    /// Invokes each `(contract, fn_name, args)` in order and returns their results.
+    /// Any failing call reverts the whole batch.
+    pub fn execute(env: Env, calls: Vec<(Address, Symbol, Vec<Val>)>) -> Vec<Val> {
+        env.current_contract_address().require_auth();
+
+        let mut results = Vec::new(&env);
+
+        for (contract, fn_name, args) in calls.iter() {
+            results.push_back(env.invoke_contract::<Val>(&contract, &fn_name, args));
+        }
+
+        extend_instance(&env);
+
+        results
+    }
+}
+
+// Passkey origins. Once set, secp256r1 signatures must come from one of these
+// clientDataJSON origins, e.g. `https://app.example` or an `android:apk-key-hash:`.
+#[contractimpl]
+impl Contract {
+    pub fn set_origins(env: Env, origins: Vec<Bytes>) {
+        env.current_contract_address().require_auth();
+
+        verify::set_origins(&env, &origins);
+
+        extend_instance(&env);
+    }
+    pub fn origins(env: Env) -> Vec<Bytes> {
+        verify::get_origins(&env)
+    }
+}
+
+// Upgrades are scheduled, then run by `update_contract_code` no sooner than
+// `UPGRADE_DELAY` ledgers later. Removing or changing an unlimited signer,
+// other than extending it, waits just as long, so it can still cancel
+// meanwhile.
+#[contractimpl]
+impl Contract {
+    pub fn pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&env)
+    }
+    /// The scheduled change to `signer_key` and the earliest ledger it can go ahead.
+    pub fn pending_removal(env: Env, signer_key: SignerKey) -> Option<PendingRemoval> {
+        upgrade::get_pending_removal(&env, &signer_key)
+    }
+}
+
+// Guardian recovery. The wallet picks its guardians; `threshold` of them can
+// add a new signer `timelock` ledgers later unless a guardian or an
+// unlimited signer cancels.
+#[contractimpl]
+impl Contract {
+    pub fn set_recovery(env: Env, guardians: Vec<Address>, threshold: u32, timelock: u32) {
+        env.current_contract_address().require_auth();
+
+        recovery::set_config(&env, guardians, threshold, timelock);
+    }
+    pub fn propose_recovery(env: Env, guardian: Address, signer: Signer) {
+        recovery::propose(&env, guardian, signer);
+    }
+    pub fn approve_recovery(env: Env, guardian: Address) {
+        recovery::approve(&env, guardian);
+    }
+    pub fn execute_recovery(env: Env) {
+        recovery::execute(&env);
+    }
+    pub fn cancel_recovery(env: Env, guardian: Option<Address>) {
+        recovery::cancel(&env, guardian);
+    }
+    pub fn recovery_config(env: Env) -> Option<RecoveryConfig> {
+        recovery::get_config(&env)
+    }
+    pub fn pending_recovery(env: Env) -> Option<Recovery> {
+        recovery::get_pending(&env)
+    }
 }
 
 #[contractimpl]
@@ -134,6 +316,9 @@ impl CustomAccountInterface for Contract {
                             SignerVal::Secp256r1(_, signer_expiration, signer_limits) => {
                                 (signer_expiration, signer_limits)
                             }
+                            SignerVal::Secp256k1(signer_expiration, signer_limits) => {
+                                (signer_expiration, signer_limits)
+                            }
                         };
 
                         verify_signer_expiration(&env, signer_expiration);
@@ -195,6 +380,19 @@ impl CustomAccountInterface for Contract {
                                 continue;
                             }
 
+                            panic_with_error!(&env, Error::SignatureKeyValueMismatch)
+                        }
+                        Signature::Secp256k1(signature) => {
+                            if let SignerKey::Secp256k1(key) = &signer_key {
+                                secp256k1::verify_signature(
+                                    &env,
+                                    &signature_payload,
+                                    key,
+                                    signature,
+                                );
+                                continue;
+                            }
+
                             panic_with_error!(&env, Error::SignatureKeyValueMismatch)
                         }
                     }
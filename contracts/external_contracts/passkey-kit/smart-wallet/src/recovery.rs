@@ -0,0 +1,200 @@
+use smart_wallet_interface::types::Signer;
+use soroban_sdk::{
+    contracterror, contracttype, panic_with_error, symbol_short, Address, Env, Symbol, Vec,
+};
+
+use crate::{
+    signer::{get_signer_val_storage, process_signer, store_signer},
+    storage::extend_instance,
+    EVENT_TAG,
+};
+
+const RECOVERY_CONFIG: Symbol = symbol_short!("rec_cfg");
+const PENDING_RECOVERY: Symbol = symbol_short!("rec_pend");
+const RECOVERY: Symbol = symbol_short!("recovery");
+
+// Roughly a day of ledgers: the least time signers get to notice and cancel
+pub const MIN_RECOVERY_TIMELOCK: u32 = 60 * 60 * 24 / 5;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum RecoveryError {
+    NotConfigured = 10,
+    InvalidThreshold = 11,
+    NotGuardian = 12,
+    RecoveryPending = 13,
+    NoRecovery = 14,
+    AlreadyApproved = 15,
+    NotReady = 16,
+    DuplicateGuardian = 17,
+    TimelockTooShort = 18,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoveryConfig {
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+    pub timelock: u32, // ledgers between reaching the threshold and execution
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recovery {
+    pub signer: Signer,
+    pub approvals: Vec<Address>,
+    pub ready_at: Option<u32>, // set once `threshold` guardians have approved
+}
+
+pub fn get_config(env: &Env) -> Option<RecoveryConfig> {
+    env.storage().instance().get(&RECOVERY_CONFIG)
+}
+
+pub fn get_pending(env: &Env) -> Option<Recovery> {
+    env.storage().instance().get(&PENDING_RECOVERY)
+}
+
+fn publish(env: &Env, stage: Symbol, recovery: &Recovery) {
+    env.events()
+        .publish((EVENT_TAG, RECOVERY, stage), recovery.clone());
+}
+
+fn require_guardian(env: &Env, config: &RecoveryConfig, guardian: &Address) {
+    guardian.require_auth();
+
+    if !config.guardians.contains(guardian) {
+        panic_with_error!(env, RecoveryError::NotGuardian)
+    }
+}
+
+fn record_approval(env: &Env, config: &RecoveryConfig, recovery: &mut Recovery, guardian: Address) {
+    if recovery.approvals.contains(&guardian) {
+        panic_with_error!(env, RecoveryError::AlreadyApproved)
+    }
+
+    recovery.approvals.push_back(guardian);
+
+    // The timelock starts when the last needed guardian approves
+    if recovery.ready_at.is_none() && recovery.approvals.len() >= config.threshold {
+        recovery.ready_at = Some(env.ledger().sequence() + config.timelock);
+    }
+
+    env.storage().instance().set(&PENDING_RECOVERY, recovery);
+    extend_instance(env);
+}
+
+pub fn set_config(env: &Env, guardians: Vec<Address>, threshold: u32, timelock: u32) {
+    let mut unique = Vec::new(env);
+
+    for guardian in guardians.iter() {
+        // The wallet can't guard itself, and each guardian counts once
+        if guardian == env.current_contract_address() {
+            panic_with_error!(env, RecoveryError::NotGuardian)
+        }
+        if unique.contains(&guardian) {
+            panic_with_error!(env, RecoveryError::DuplicateGuardian)
+        }
+        unique.push_back(guardian);
+    }
+
+    if threshold == 0 || threshold > guardians.len() {
+        panic_with_error!(env, RecoveryError::InvalidThreshold)
+    }
+    if timelock < MIN_RECOVERY_TIMELOCK {
+        panic_with_error!(env, RecoveryError::TimelockTooShort)
+    }
+
+    let config = RecoveryConfig {
+        guardians,
+        threshold,
+        timelock,
+    };
+
+    // A new guardian set voids whatever the old one had started
+    env.storage().instance().remove(&PENDING_RECOVERY);
+    env.storage().instance().set(&RECOVERY_CONFIG, &config);
+    extend_instance(env);
+
+    env.events()
+        .publish((EVENT_TAG, RECOVERY, symbol_short!("config")), config);
+}
+
+pub fn propose(env: &Env, guardian: Address, signer: Signer) {
+    let config = get_config(env).unwrap_or_else(|| panic_with_error!(env, RecoveryError::NotConfigured));
+
+    require_guardian(env, &config, &guardian);
+
+    if get_pending(env).is_some() {
+        panic_with_error!(env, RecoveryError::RecoveryPending)
+    }
+
+    let mut recovery = Recovery {
+        signer,
+        approvals: Vec::new(env),
+        ready_at: None,
+    };
+
+    record_approval(env, &config, &mut recovery, guardian);
+    publish(env, symbol_short!("propose"), &recovery);
+}
+
+pub fn approve(env: &Env, guardian: Address) {
+    let config = get_config(env).unwrap_or_else(|| panic_with_error!(env, RecoveryError::NotConfigured));
+
+    require_guardian(env, &config, &guardian);
+
+    let mut recovery = get_pending(env).unwrap_or_else(|| panic_with_error!(env, RecoveryError::NoRecovery));
+
+    record_approval(env, &config, &mut recovery, guardian);
+    publish(env, symbol_short!("approve"), &recovery);
+}
+
+pub fn execute(env: &Env) {
+    let recovery = get_pending(env).unwrap_or_else(|| panic_with_error!(env, RecoveryError::NoRecovery));
+
+    match recovery.ready_at {
+        Some(ready_at) if env.ledger().sequence() >= ready_at => {}
+        _ => panic_with_error!(env, RecoveryError::NotReady),
+    }
+
+    let (signer_key, signer_val, signer_storage) = process_signer(recovery.signer.clone());
+    let exists = get_signer_val_storage(env, &signer_key, false).is_some();
+
+    store_signer(env, &signer_key, &signer_val, &signer_storage, exists);
+
+    env.storage().instance().remove(&PENDING_RECOVERY);
+    extend_instance(env);
+
+    publish(env, symbol_short!("execute"), &recovery);
+
+    let action = if exists {
+        symbol_short!("update")
+    } else {
+        symbol_short!("add")
+    };
+
+    env.events()
+        .publish((EVENT_TAG, action, signer_key), (signer_val, signer_storage));
+}
+
+/// A guardian, or the wallet itself when `guardian` is `None`. Limited
+/// signers can't authorize the wallet for this, so only unlimited ones can.
+pub fn cancel(env: &Env, guardian: Option<Address>) {
+    match guardian {
+        Some(guardian) => {
+            let config = get_config(env)
+                .unwrap_or_else(|| panic_with_error!(env, RecoveryError::NotConfigured));
+
+            require_guardian(env, &config, &guardian);
+        }
+        None => env.current_contract_address().require_auth(),
+    }
+
+    let recovery = get_pending(env).unwrap_or_else(|| panic_with_error!(env, RecoveryError::NoRecovery));
+
+    env.storage().instance().remove(&PENDING_RECOVERY);
+    extend_instance(env);
+
+    publish(env, symbol_short!("cancel"), &recovery);
+}
@@ -1,10 +1,15 @@
 use smart_wallet_interface::{
+    secp256k1,
     types::{Error, Signatures, Signer, SignerExpiration, SignerKey, SignerStorage, SignerVal},
     PolicyClient,
 };
-use soroban_sdk::{auth::Context, panic_with_error, vec, Env, Vec};
+use soroban_sdk::{auth::Context, panic_with_error, symbol_short, vec, Env, Symbol, Vec};
 
-use crate::{context::verify_context, storage::extend_signer_key};
+use crate::{
+    context::verify_context,
+    group::group_threshold_met,
+    storage::{extend_signer_index, extend_signer_key},
+};
 
 pub fn process_signer(signer: Signer) -> (SignerKey, SignerVal, SignerStorage) {
     match signer {
@@ -23,9 +28,93 @@ pub fn process_signer(signer: Signer) -> (SignerKey, SignerVal, SignerStorage) {
             SignerVal::Secp256r1(public_key, signer_expiration, signer_limits),
             signer_storage,
         ),
+        Signer::Secp256k1(key, signer_expiration, signer_limits, signer_storage) => (
+            SignerKey::Secp256k1(key),
+            SignerVal::Secp256k1(signer_expiration, signer_limits),
+            signer_storage,
+        ),
+    }
+}
+
+// Every signer key ever stored, in the order it was first added. Kept in
+// persistent storage, as expired session signers stay in it until
+// `list_signers` prunes them and the instance is loaded on every call.
+pub const SIGNERS: Symbol = symbol_short!("signers");
+
+pub fn build_signer(
+    env: &Env,
+    signer_key: SignerKey,
+    signer_val: SignerVal,
+    signer_storage: SignerStorage,
+) -> Signer {
+    match (signer_key, signer_val) {
+        (SignerKey::Policy(policy), SignerVal::Policy(signer_expiration, signer_limits)) => {
+            Signer::Policy(policy, signer_expiration, signer_limits, signer_storage)
+        }
+        (SignerKey::Ed25519(public_key), SignerVal::Ed25519(signer_expiration, signer_limits)) => {
+            Signer::Ed25519(public_key, signer_expiration, signer_limits, signer_storage)
+        }
+        (
+            SignerKey::Secp256r1(id),
+            SignerVal::Secp256r1(public_key, signer_expiration, signer_limits),
+        ) => Signer::Secp256r1(
+            id,
+            public_key,
+            signer_expiration,
+            signer_limits,
+            signer_storage,
+        ),
+        (SignerKey::Secp256k1(key), SignerVal::Secp256k1(signer_expiration, signer_limits)) => {
+            Signer::Secp256k1(key, signer_expiration, signer_limits, signer_storage)
+        }
+        _ => panic_with_error!(env, Error::SignatureKeyValueMismatch),
+    }
+}
+
+fn get_signer_index(env: &Env) -> Vec<SignerKey> {
+    env.storage()
+        .persistent()
+        .get::<Symbol, Vec<SignerKey>>(&SIGNERS)
+        .unwrap_or(Vec::new(env))
+}
+
+fn set_signer_index(env: &Env, signer_keys: &Vec<SignerKey>) {
+    env.storage()
+        .persistent()
+        .set::<Symbol, Vec<SignerKey>>(&SIGNERS, signer_keys);
+    extend_signer_index(env);
+}
+
+pub fn unindex_signer(env: &Env, signer_key: &SignerKey) {
+    let mut signer_keys = get_signer_index(env);
+
+    if let Some(index) = signer_keys.first_index_of(signer_key) {
+        signer_keys.remove(index);
+        set_signer_index(env, &signer_keys);
     }
 }
 
+// Live signers, dropping index entries whose temporary storage has expired
+pub fn list_signers(env: &Env) -> Vec<Signer> {
+    let signer_keys = get_signer_index(env);
+    let mut live_keys = Vec::new(env);
+    let mut signers = Vec::new(env);
+
+    for signer_key in signer_keys.iter() {
+        if let Some((signer_val, signer_storage)) = get_signer_val_storage(env, &signer_key, false)
+        {
+            live_keys.push_back(signer_key.clone());
+            signers.push_back(build_signer(env, signer_key, signer_val, signer_storage));
+        }
+    }
+
+    if live_keys.len() != signer_keys.len() {
+        set_signer_index(env, &live_keys);
+    }
+
+    signers
+}
+
 pub fn store_signer(
     env: &Env,
     signer_key: &SignerKey,
@@ -33,6 +122,10 @@ pub fn store_signer(
     signer_storage: &SignerStorage,
     update: bool,
 ) {
+    if let SignerKey::Secp256k1(key) = signer_key {
+        secp256k1::verify_key(env, key);
+    }
+
     // Include this before the `.set` calls so it doesn't read them as previous values
     let previous_signer_val_and_storage: Option<(SignerVal, SignerStorage)> =
         get_signer_val_storage(env, signer_key, false);
@@ -57,6 +150,14 @@ pub fn store_signer(
 
     extend_signer_key(env, signer_key, is_persistent);
 
+    // A temporary signer that expired is still indexed, so re-adding it keeps its place
+    let mut signer_keys = get_signer_index(env);
+
+    if !signer_keys.contains(signer_key) {
+        signer_keys.push_back(signer_key.clone());
+        set_signer_index(env, &signer_keys);
+    }
+
     match previous_signer_val_and_storage {
         Some((_, previous_signer_storage)) => {
             // Panic if the signer key already exists and we're not update it
@@ -170,6 +271,11 @@ pub fn verify_signer_limit_keys(
                     signer_key,
                     &vec![env, context.clone()],
                 );
+            } else if let SignerKey::Group(name) = &signer_limits_key {
+                // A group is met once enough of its members are in the signatures map
+                if !group_threshold_met(env, name, signatures, context) {
+                    panic_with_error!(env, Error::FailedSignerLimits)
+                }
                 // For every other SignerLimits key, it must exist in the signatures map and thus exist as a signer on the smart wallet
             } else if !signatures.0.contains_key(signer_limits_key.clone()) {
                 // if any required key is missing this contract invocation is invalid
@@ -177,4 +283,4 @@ pub fn verify_signer_limit_keys(
             }
         }
     }
-}
\ No newline at end of file
+}
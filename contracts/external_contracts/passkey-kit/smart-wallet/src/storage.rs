@@ -1,5 +1,7 @@
 use smart_wallet_interface::types::SignerKey;
-use soroban_sdk::Env;
+use soroban_sdk::{Env, Symbol};
+
+use crate::{group::GROUP, signer::SIGNERS};
 
 const WEEK_OF_LEDGERS: u32 = 60 * 60 * 24 / 5 * 7;
 
@@ -26,4 +28,20 @@ pub fn extend_signer_key(env: &Env, signer_key: &SignerKey, persistent: bool) {
             max_ttl,
         );
     }
-}
\ No newline at end of file
+}
+pub fn extend_signer_index(env: &Env) {
+    let max_ttl = env.storage().max_ttl();
+
+    env.storage()
+        .persistent()
+        .extend_ttl::<Symbol>(&SIGNERS, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+}
+pub fn extend_group(env: &Env, name: &Symbol) {
+    let max_ttl = env.storage().max_ttl();
+
+    env.storage().persistent().extend_ttl::<(Symbol, Symbol)>(
+        &(GROUP, name.clone()),
+        max_ttl - WEEK_OF_LEDGERS,
+        max_ttl,
+    );
+}
@@ -6,12 +6,12 @@ extern crate std;
 use crate::{Contract, ContractClient};
 use ed25519_dalek::{Keypair, Signer as _};
 use example_contract::{Contract as ExampleContract, ContractClient as ExampleContractClient};
-use sample_policy::Contract as PolicyContract;
+use sample_policy::{Contract as PolicyContract, ContractClient as PolicyContractClient, SpendLimit};
 use smart_wallet_interface::types::{
     Signature, Signatures, Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage,
 };
 use soroban_sdk::{
-    map,
+    map, symbol_short,
     testutils::EnvTestConfig,
     token, vec,
     xdr::{
@@ -139,6 +139,19 @@ fn test() {
     // Policy
     let sample_policy_address = env.register(PolicyContract, ());
     let sample_policy_signer_key = SignerKey::Policy(sample_policy_address.clone());
+
+    PolicyContractClient::new(&env, &sample_policy_address)
+        .mock_all_auths()
+        .set_limit(
+            &wallet_address,
+            &sac_address,
+            &symbol_short!("transfer"),
+            &SpendLimit {
+                max_amount: amount + evil_amount,
+                window: 17_280,
+                destinations: Some(vec![&env, sac_address.clone()]),
+            },
+        );
     //
 
     // Add signers to smart wallet
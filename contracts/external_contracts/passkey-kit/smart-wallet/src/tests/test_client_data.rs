@@ -0,0 +1,236 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{verify::ClientDataError, Contract, ContractClient};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use p256::ecdsa::{signature::Signer as _, Signature as P256Signature, SigningKey};
+use smart_wallet_interface::{
+    client_data::parse,
+    types::{
+        Error, Secp256r1Signature, Signature, Signatures, Signer, SignerExpiration, SignerKey,
+        SignerLimits, SignerStorage,
+    },
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    bytes, map, symbol_short,
+    testutils::Address as _,
+    vec, Address, Bytes, BytesN, Env, IntoVal, InvokeError,
+};
+use std::{format, string::String};
+
+const PAYLOAD: [u8; 32] = [7; 32];
+
+// Chrome on Android, signing for a web origin
+const ANDROID_WEB: &str = r#"{"type":"webauthn.get","challenge":"{challenge}","origin":"https://flashpool.app","crossOrigin":false,"other_keys_can_be_added_here":"do not compare clientDataJSON against a template. See https://goo.gl/yabPex"}"#;
+
+// An Android app through Credential Manager, identified by its signing certificate hash
+const ANDROID_APP: &str = r#"{"type":"webauthn.get","challenge":"{challenge}","origin":"android:apk-key-hash:kT2KqNnDXrE9aR-uq5qVvZ3M4hJ0rIqVYm2Zb8C8uVg","androidPackageName":"app.flashpool.wallet"}"#;
+
+// Safari on iOS, or an iOS app through AuthenticationServices
+const IOS: &str =
+    r#"{"type":"webauthn.get","challenge":"{challenge}","origin":"https://flashpool.app"}"#;
+
+fn client_data_json(template: &str, payload: &[u8; 32]) -> String {
+    template.replace("{challenge}", &URL_SAFE_NO_PAD.encode(payload))
+}
+
+// Well past the old 1024-byte buffer, with nested members that must be skipped
+fn large_client_data_json(payload: &[u8; 32]) -> String {
+    format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://flashpool.app","tokenBinding":{{"status":"present","id":"{}"}},"extensions":[{{"challenge":"not-this-one"}},"{}"]}}"#,
+        URL_SAFE_NO_PAD.encode(payload),
+        "x".repeat(600),
+        "y".repeat(1200),
+    )
+}
+
+fn sign(env: &Env, key: &SigningKey, client_data_json: &str) -> Signature {
+    let authenticator_data = [5u8; 37];
+    let mut message = std::vec::Vec::from(authenticator_data);
+
+    message.extend_from_slice(
+        &env.crypto()
+            .sha256(&Bytes::from_slice(env, client_data_json.as_bytes()))
+            .to_array(),
+    );
+
+    let signature: P256Signature = key.sign(&message);
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    Signature::Secp256r1(Secp256r1Signature {
+        authenticator_data: Bytes::from_slice(env, &authenticator_data),
+        client_data_json: Bytes::from_slice(env, client_data_json.as_bytes()),
+        signature: BytesN::from_array(env, &signature.to_bytes().into()),
+    })
+}
+
+fn check(
+    env: &Env,
+    wallet_address: &Address,
+    signature: Signature,
+) -> Result<(), Result<Error, InvokeError>> {
+    let contexts = vec![
+        env,
+        Context::Contract(ContractContext {
+            contract: Address::generate(env),
+            fn_name: symbol_short!("transfer"),
+            args: vec![env, wallet_address.into_val(env)],
+        }),
+    ];
+
+    env.try_invoke_contract_check_auth::<Error>(
+        wallet_address,
+        &BytesN::from_array(env, &PAYLOAD),
+        Signatures(map![
+            env,
+            (SignerKey::Secp256r1(bytes!(env, 0x0102)), signature)
+        ])
+        .into_val(env),
+        &contexts,
+    )
+}
+
+#[test]
+fn test_parse_samples() {
+    let env = Env::default();
+    let challenge = Bytes::from_slice(&env, URL_SAFE_NO_PAD.encode(PAYLOAD).as_bytes());
+
+    for (json, origin) in [
+        (
+            client_data_json(ANDROID_WEB, &PAYLOAD),
+            "https://flashpool.app",
+        ),
+        (
+            client_data_json(ANDROID_APP, &PAYLOAD),
+            "android:apk-key-hash:kT2KqNnDXrE9aR-uq5qVvZ3M4hJ0rIqVYm2Zb8C8uVg",
+        ),
+        (client_data_json(IOS, &PAYLOAD), "https://flashpool.app"),
+        (large_client_data_json(&PAYLOAD), "https://flashpool.app"),
+    ] {
+        let client_data = parse(&Bytes::from_slice(&env, json.as_bytes())).unwrap();
+
+        assert_eq!(client_data.challenge, Some(challenge.clone()));
+        assert_eq!(
+            client_data.type_,
+            Some(Bytes::from_slice(&env, b"webauthn.get"))
+        );
+        assert_eq!(
+            client_data.origin,
+            Some(Bytes::from_slice(&env, origin.as_bytes()))
+        );
+    }
+}
+
+#[test]
+fn test_parse_rejects_malformed() {
+    let env = Env::default();
+
+    for json in [
+        "",
+        "[]",
+        r#""webauthn.get""#,
+        r#"{"type":"webauthn.get","challenge":"abc""#,
+        r#"{"type":"webauthn.get","challenge":"abc"} {}"#,
+        r#"{"type":"webauthn.get","challenge":"abc","challenge":"def"}"#,
+    ] {
+        assert!(parse(&Bytes::from_slice(&env, json.as_bytes())).is_none());
+    }
+
+    // Escapes are skipped over but never unescaped
+    let client_data = parse(&Bytes::from_slice(
+        &env,
+        br#"{"note":"a \"quoted\" }","origin":"https:\/\/flashpool.app"}"#,
+    ))
+    .unwrap();
+
+    assert_eq!(client_data.challenge, None);
+    assert_eq!(
+        client_data.origin,
+        Some(Bytes::from_slice(&env, br#"https:\/\/flashpool.app"#))
+    );
+}
+
+#[test]
+fn test_origin_allowlist() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let key = SigningKey::from_bytes(&[1; 32].into()).unwrap();
+    let wallet_address = env.register(
+        Contract,
+        (Signer::Secp256r1(
+            bytes!(&env, 0x0102),
+            BytesN::from_array(
+                &env,
+                key.verifying_key()
+                    .to_encoded_point(false)
+                    .as_bytes()
+                    .try_into()
+                    .unwrap(),
+            ),
+            SignerExpiration(None),
+            SignerLimits(None),
+            SignerStorage::Persistent,
+        ),),
+    );
+    let wallet_client = ContractClient::new(&env, &wallet_address);
+
+    let android_web = sign(&env, &key, &client_data_json(ANDROID_WEB, &PAYLOAD));
+    let android_app = sign(&env, &key, &client_data_json(ANDROID_APP, &PAYLOAD));
+    let ios = sign(&env, &key, &client_data_json(IOS, &PAYLOAD));
+    let large = sign(&env, &key, &large_client_data_json(&PAYLOAD));
+
+    // Without an allowlist any origin goes
+    assert_eq!(check(&env, &wallet_address, android_web.clone()), Ok(()));
+    assert_eq!(check(&env, &wallet_address, android_app.clone()), Ok(()));
+    assert_eq!(check(&env, &wallet_address, ios.clone()), Ok(()));
+    assert_eq!(check(&env, &wallet_address, large.clone()), Ok(()));
+
+    // Only the web app from here on
+    wallet_client.set_origins(&vec![
+        &env,
+        Bytes::from_slice(&env, b"https://flashpool.app"),
+    ]);
+
+    assert_eq!(env.auths()[0].0, wallet_address);
+    assert_eq!(check(&env, &wallet_address, android_web), Ok(()));
+    assert_eq!(check(&env, &wallet_address, ios), Ok(()));
+    assert_eq!(check(&env, &wallet_address, large), Ok(()));
+    assert_eq!(
+        check(&env, &wallet_address, android_app.clone()),
+        Err(Err(InvokeError::Contract(
+            ClientDataError::OriginNotAllowed as u32
+        )))
+    );
+
+    // Clearing the list lifts the restriction
+    wallet_client.set_origins(&vec![&env]);
+
+    assert_eq!(wallet_client.origins(), vec![&env]);
+    assert_eq!(check(&env, &wallet_address, android_app), Ok(()));
+
+    // Registrations and other challenges are still refused
+    let create = IOS.replace("webauthn.get", "webauthn.create");
+
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            sign(&env, &key, &client_data_json(&create, &PAYLOAD))
+        ),
+        Err(Err(InvokeError::Contract(
+            ClientDataError::InvalidType as u32
+        )))
+    );
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            sign(&env, &key, &client_data_json(IOS, &[8; 32]))
+        ),
+        Err(Ok(Error::ClientDataJsonChallengeIncorrect))
+    );
+}
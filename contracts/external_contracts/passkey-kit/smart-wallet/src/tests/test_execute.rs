@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{Contract, ContractClient};
+use smart_wallet_interface::types::{Signer, SignerExpiration, SignerLimits, SignerStorage};
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction},
+    token, vec, Address, BytesN, Env, FromVal, IntoVal, Symbol, Val, Vec,
+};
+
+fn transfer(
+    env: &Env,
+    sac: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) -> (Address, Symbol, Vec<Val>) {
+    (
+        sac.clone(),
+        Symbol::new(env, "transfer"),
+        vec![
+            env,
+            from.into_val(env),
+            to.into_val(env),
+            amount.into_val(env),
+        ],
+    )
+}
+
+fn setup(env: &Env) -> (ContractClient<'_>, token::Client<'_>) {
+    env.mock_all_auths();
+
+    let wallet_address = env.register(
+        Contract,
+        (Signer::Ed25519(
+            BytesN::from_array(env, &[1; 32]),
+            SignerExpiration(None),
+            SignerLimits(None),
+            SignerStorage::Persistent,
+        ),),
+    );
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(env));
+
+    token::StellarAssetClient::new(env, &sac.address()).mint(&wallet_address, &1_000);
+
+    (
+        ContractClient::new(env, &wallet_address),
+        token::Client::new(env, &sac.address()),
+    )
+}
+
+#[test]
+fn test_execute_batch() {
+    let env = Env::default();
+    let (wallet_client, token_client) = setup(&env);
+    let wallet_address = wallet_client.address.clone();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    let results = wallet_client.execute(&vec![
+        &env,
+        transfer(&env, &token_client.address, &wallet_address, &alice, 100),
+        transfer(&env, &token_client.address, &wallet_address, &bob, 200),
+        (
+            token_client.address.clone(),
+            Symbol::new(&env, "balance"),
+            vec![&env, wallet_address.into_val(&env)],
+        ),
+    ]);
+
+    // One wallet authorization covers the whole batch
+    let auths = env.auths();
+
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, wallet_address);
+    assert!(matches!(
+        &auths[0].1.function,
+        AuthorizedFunction::Contract((contract, fn_name, _))
+            if *contract == wallet_address && *fn_name == Symbol::new(&env, "execute")
+    ));
+
+    assert_eq!(results.len(), 3);
+    assert!(results.get_unchecked(0).is_void());
+    assert_eq!(i128::from_val(&env, &results.get_unchecked(2)), 700);
+    assert_eq!(token_client.balance(&alice), 100);
+    assert_eq!(token_client.balance(&bob), 200);
+}
+
+#[test]
+fn test_execute_atomic() {
+    let env = Env::default();
+    let (wallet_client, token_client) = setup(&env);
+    let wallet_address = wallet_client.address.clone();
+    let alice = Address::generate(&env);
+
+    // The second transfer overdraws, so the first must not stick either
+    assert!(wallet_client
+        .try_execute(&vec![
+            &env,
+            transfer(&env, &token_client.address, &wallet_address, &alice, 600),
+            transfer(&env, &token_client.address, &wallet_address, &alice, 600),
+        ])
+        .is_err());
+
+    assert_eq!(token_client.balance(&alice), 0);
+    assert_eq!(token_client.balance(&wallet_address), 1_000);
+}
@@ -0,0 +1,255 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{
+    group::{group_threshold_met, GroupError},
+    Contract, ContractClient,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer as _};
+use smart_wallet_interface::types::{
+    Error, Signature, Signatures, Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage,
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    map, symbol_short,
+    testutils::{Address as _, Ledger},
+    vec, Address, BytesN, Env, IntoVal, Map, Vec,
+};
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+
+    Keypair { secret, public }
+}
+
+fn signer_key(env: &Env, keypair: &Keypair) -> SignerKey {
+    SignerKey::Ed25519(BytesN::from_array(env, &keypair.public.to_bytes()))
+}
+
+fn ed25519_signer(env: &Env, keypair: &Keypair, signer_limits: SignerLimits) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(env, &keypair.public.to_bytes()),
+        SignerExpiration(None),
+        signer_limits,
+        SignerStorage::Persistent,
+    )
+}
+
+// A team wallet whose three members may only act on `manager`, two at a time
+fn setup(env: &Env) -> (ContractClient<'_>, std::vec::Vec<Keypair>, Address) {
+    env.mock_all_auths();
+
+    let wallet_address = env.register(
+        Contract,
+        (ed25519_signer(env, &keypair(1), SignerLimits(None)),),
+    );
+    let wallet_client = ContractClient::new(env, &wallet_address);
+    let manager = Address::generate(env);
+    let members = std::vec![keypair(2), keypair(3), keypair(4)];
+    let mut member_keys = Vec::new(env);
+
+    for member in members.iter() {
+        member_keys.push_back(signer_key(env, member));
+        wallet_client.add_signer(&ed25519_signer(
+            env,
+            member,
+            SignerLimits(Some(map![
+                env,
+                (
+                    manager.clone(),
+                    Some(vec![env, SignerKey::Group(symbol_short!("team"))])
+                )
+            ])),
+        ));
+    }
+
+    wallet_client.set_group(&symbol_short!("team"), &member_keys, &2);
+
+    (wallet_client, members, manager)
+}
+
+fn check(
+    env: &Env,
+    wallet_address: &Address,
+    contract: &Address,
+    signers: &[&Keypair],
+) -> Result<(), Result<Error, soroban_sdk::InvokeError>> {
+    let payload = BytesN::from_array(env, &[7; 32]);
+    let mut signatures = Map::new(env);
+
+    for signer in signers {
+        signatures.set(
+            signer_key(env, signer),
+            Signature::Ed25519(BytesN::from_array(
+                env,
+                &signer.sign(&payload.to_array()).to_bytes(),
+            )),
+        );
+    }
+
+    let contexts = vec![
+        env,
+        Context::Contract(ContractContext {
+            contract: contract.clone(),
+            fn_name: symbol_short!("set_admin"),
+            args: vec![env, wallet_address.into_val(env)],
+        }),
+    ];
+
+    env.try_invoke_contract_check_auth::<Error>(
+        wallet_address,
+        &payload,
+        Signatures(signatures).into_val(env),
+        &contexts,
+    )
+}
+
+#[test]
+fn test_group_threshold() {
+    let env = Env::default();
+    let (wallet_client, members, manager) = setup(&env);
+    let wallet_address = wallet_client.address.clone();
+
+    assert_eq!(
+        check(&env, &wallet_address, &manager, &[&members[0]]),
+        Err(Ok(Error::FailedSignerLimits))
+    );
+    assert_eq!(
+        check(&env, &wallet_address, &manager, &[&members[0], &members[2]]),
+        Ok(())
+    );
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            &manager,
+            &[&members[0], &members[1], &members[2]]
+        ),
+        Ok(())
+    );
+
+    // Members still can't act outside their limits
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            &Address::generate(&env),
+            &[&members[0], &members[1]]
+        ),
+        Err(Ok(Error::MissingContext))
+    );
+
+    // Raising the threshold takes effect immediately
+    let member_keys = wallet_client.group(&symbol_short!("team")).unwrap().members;
+
+    wallet_client.set_group(&symbol_short!("team"), &member_keys, &3);
+
+    assert_eq!(
+        check(&env, &wallet_address, &manager, &[&members[0], &members[2]]),
+        Err(Ok(Error::FailedSignerLimits))
+    );
+
+    // Without the group nothing is met
+    wallet_client.remove_group(&symbol_short!("team"));
+
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            &manager,
+            &[&members[0], &members[1], &members[2]]
+        ),
+        Err(Ok(Error::FailedSignerLimits))
+    );
+}
+
+#[test]
+fn test_group_config() {
+    let env = Env::default();
+    let (wallet_client, members, _) = setup(&env);
+    let key = signer_key(&env, &members[0]);
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+    assert_eq!(
+        wallet_client.try_set_group(&symbol_short!("t2"), &vec![&env, key.clone()], &2),
+        Err(Ok(GroupError::InvalidGroup.into()))
+    );
+    assert_eq!(
+        wallet_client.try_set_group(
+            &symbol_short!("t2"),
+            &vec![&env, key.clone(), key.clone()],
+            &1
+        ),
+        Err(Ok(GroupError::InvalidGroup.into()))
+    );
+    assert_eq!(
+        wallet_client.try_set_group(
+            &symbol_short!("t2"),
+            &vec![&env, key, SignerKey::Group(symbol_short!("team"))],
+            &1
+        ),
+        Err(Ok(GroupError::InvalidGroup.into()))
+    );
+    assert_eq!(
+        wallet_client.try_remove_group(&symbol_short!("t2")),
+        Err(Ok(GroupError::InvalidGroup.into()))
+    );
+}
+
+#[test]
+fn test_group_members_must_qualify() {
+    let env = Env::default();
+    let (wallet_client, members, manager) = setup(&env);
+    let wallet_address = wallet_client.address.clone();
+    let team = symbol_short!("team");
+    let context = Context::Contract(ContractContext {
+        contract: manager.clone(),
+        fn_name: symbol_short!("set_admin"),
+        args: vec![&env, wallet_address.into_val(&env)],
+    });
+    let met = |signers: &[&Keypair]| {
+        let mut signatures = Map::new(&env);
+
+        for signer in signers {
+            signatures.set(signer_key(&env, signer), Signature::Policy);
+        }
+
+        env.as_contract(&wallet_address, || {
+            group_threshold_met(&env, &team, &Signatures(signatures), &context)
+        })
+    };
+
+    assert!(met(&[&members[0], &members[1]]));
+
+    // A member whose own limits don't reach the context doesn't count
+    wallet_client.update_signer(&ed25519_signer(
+        &env,
+        &members[1],
+        SignerLimits(Some(map![&env, (Address::generate(&env), None)])),
+    ));
+
+    assert!(!met(&[&members[0], &members[1]]));
+    assert_eq!(
+        check(&env, &wallet_address, &manager, &[&members[0], &members[1]]),
+        Err(Ok(Error::FailedSignerLimits))
+    );
+
+    // Nor does an expired one
+    wallet_client.update_signer(&Signer::Ed25519(
+        BytesN::from_array(&env, &members[2].public.to_bytes()),
+        SignerExpiration(Some(env.ledger().sequence())),
+        SignerLimits(Some(map![
+            &env,
+            (manager.clone(), Some(vec![&env, SignerKey::Group(team.clone())]))
+        ])),
+        SignerStorage::Persistent,
+    ));
+
+    assert!(met(&[&members[0], &members[2]]));
+
+    env.ledger().with_mut(|li| li.sequence_number += 1);
+
+    assert!(!met(&[&members[0], &members[2]]));
+}
@@ -0,0 +1,266 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{
+    recovery::{Recovery, RecoveryError, MIN_RECOVERY_TIMELOCK as TIMELOCK},
+    Contract, ContractClient,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer as _};
+use smart_wallet_interface::types::{
+    Error, Signature, Signatures, Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage,
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    map, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    vec, Address, BytesN, Env, IntoVal, Symbol, Vec,
+};
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+
+    Keypair { secret, public }
+}
+
+fn ed25519_signer(env: &Env, keypair: &Keypair, signer_limits: SignerLimits) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(env, &keypair.public.to_bytes()),
+        SignerExpiration(None),
+        signer_limits,
+        SignerStorage::Persistent,
+    )
+}
+
+fn setup(env: &Env) -> (ContractClient<'_>, Vec<Address>) {
+    env.mock_all_auths();
+
+    let wallet_address = env.register(
+        Contract,
+        (ed25519_signer(env, &keypair(1), SignerLimits(None)),),
+    );
+    let wallet_client = ContractClient::new(env, &wallet_address);
+
+    let guardians = vec![
+        env,
+        Address::generate(env),
+        Address::generate(env),
+        Address::generate(env),
+    ];
+
+    wallet_client.set_recovery(&guardians, &2, &TIMELOCK);
+
+    (wallet_client, guardians)
+}
+
+fn last_stage(env: &Env) -> Vec<soroban_sdk::Val> {
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    topics
+}
+
+#[test]
+fn test_recovery_config() {
+    let env = Env::default();
+    let (wallet_client, guardians) = setup(&env);
+
+    assert_eq!(
+        wallet_client.try_set_recovery(&guardians, &0, &TIMELOCK),
+        Err(Ok(RecoveryError::InvalidThreshold.into()))
+    );
+    assert_eq!(
+        wallet_client.try_set_recovery(&guardians, &4, &TIMELOCK),
+        Err(Ok(RecoveryError::InvalidThreshold.into()))
+    );
+
+    let mut duplicated = guardians.clone();
+    duplicated.push_back(guardians.get_unchecked(0));
+
+    assert_eq!(
+        wallet_client.try_set_recovery(&duplicated, &2, &TIMELOCK),
+        Err(Ok(RecoveryError::DuplicateGuardian.into()))
+    );
+    assert_eq!(
+        wallet_client.try_set_recovery(&guardians, &2, &(TIMELOCK - 1)),
+        Err(Ok(RecoveryError::TimelockTooShort.into()))
+    );
+
+    // Only the wallet itself may change its guardians
+    wallet_client.set_recovery(&guardians, &2, &TIMELOCK);
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+
+    let config = wallet_client.recovery_config().unwrap();
+
+    assert_eq!(config.threshold, 2);
+    assert_eq!(config.timelock, TIMELOCK);
+}
+
+#[test]
+fn test_recovery_flow() {
+    let env = Env::default();
+    let (wallet_client, guardians) = setup(&env);
+    let g1 = guardians.get_unchecked(0);
+    let g2 = guardians.get_unchecked(1);
+    let new_signer = ed25519_signer(&env, &keypair(2), SignerLimits(None));
+
+    assert_eq!(
+        wallet_client.try_propose_recovery(&Address::generate(&env), &new_signer),
+        Err(Ok(RecoveryError::NotGuardian.into()))
+    );
+
+    wallet_client.propose_recovery(&g1, &new_signer);
+    assert_eq!(env.auths()[0].0, g1);
+    assert_eq!(
+        last_stage(&env),
+        (symbol_short!("sw_v1"), symbol_short!("recovery"), symbol_short!("propose")).into_val(&env)
+    );
+    assert_eq!(
+        wallet_client.try_propose_recovery(&g2, &new_signer),
+        Err(Ok(RecoveryError::RecoveryPending.into()))
+    );
+    assert_eq!(
+        wallet_client.try_approve_recovery(&g1),
+        Err(Ok(RecoveryError::AlreadyApproved.into()))
+    );
+    assert_eq!(
+        wallet_client.try_execute_recovery(),
+        Err(Ok(RecoveryError::NotReady.into()))
+    );
+
+    // The second approval meets the threshold and starts the timelock
+    wallet_client.approve_recovery(&g2);
+
+    let Recovery { approvals, ready_at, .. } = wallet_client.pending_recovery().unwrap();
+
+    assert_eq!(approvals, vec![&env, g1, g2]);
+    assert_eq!(ready_at, Some(env.ledger().sequence() + TIMELOCK));
+    assert_eq!(
+        wallet_client.try_execute_recovery(),
+        Err(Ok(RecoveryError::NotReady.into()))
+    );
+
+    env.ledger().with_mut(|li| li.sequence_number += TIMELOCK);
+
+    wallet_client.execute_recovery();
+
+    assert_eq!(
+        last_stage(&env),
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("add"),
+            SignerKey::Ed25519(BytesN::from_array(&env, &keypair(2).public.to_bytes()))
+        )
+            .into_val(&env)
+    );
+    assert_eq!(wallet_client.pending_recovery(), None);
+    assert_eq!(
+        wallet_client.try_add_signer(&new_signer),
+        Err(Ok(Error::AlreadyExists.into()))
+    );
+}
+
+#[test]
+fn test_recovery_cancel() {
+    let env = Env::default();
+    let (wallet_client, guardians) = setup(&env);
+    let new_signer = ed25519_signer(&env, &keypair(2), SignerLimits(None));
+
+    assert_eq!(
+        wallet_client.try_cancel_recovery(&None),
+        Err(Ok(RecoveryError::NoRecovery.into()))
+    );
+
+    // The wallet itself can cancel...
+    wallet_client.propose_recovery(&guardians.get_unchecked(0), &new_signer);
+    wallet_client.approve_recovery(&guardians.get_unchecked(1));
+    wallet_client.cancel_recovery(&None);
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+    assert_eq!(
+        last_stage(&env),
+        (symbol_short!("sw_v1"), symbol_short!("recovery"), symbol_short!("cancel")).into_val(&env)
+    );
+
+    // ...and so can any guardian, but nobody else
+    wallet_client.propose_recovery(&guardians.get_unchecked(0), &new_signer);
+    assert_eq!(
+        wallet_client.try_cancel_recovery(&Some(Address::generate(&env))),
+        Err(Ok(RecoveryError::NotGuardian.into()))
+    );
+    wallet_client.cancel_recovery(&Some(guardians.get_unchecked(2)));
+
+    assert_eq!(env.auths()[0].0, guardians.get_unchecked(2));
+
+    env.ledger().with_mut(|li| li.sequence_number += TIMELOCK);
+
+    assert_eq!(
+        wallet_client.try_execute_recovery(),
+        Err(Ok(RecoveryError::NoRecovery.into()))
+    );
+}
+
+#[test]
+fn test_recovery_self_management() {
+    let env = Env::default();
+    let (wallet_client, guardians) = setup(&env);
+    let wallet_address = wallet_client.address.clone();
+
+    // A signer limited to the wallet itself
+    let limited = keypair(3);
+    let limited_key = SignerKey::Ed25519(BytesN::from_array(&env, &limited.public.to_bytes()));
+
+    wallet_client.add_signer(&ed25519_signer(
+        &env,
+        &limited,
+        SignerLimits(Some(map![&env, (wallet_address.clone(), None)])),
+    ));
+
+    let check = |fn_name: &str, args: Vec<soroban_sdk::Val>| {
+        let payload = BytesN::from_array(&env, &[7; 32]);
+        let signature = limited.sign(&payload.to_array()).to_bytes();
+        let signatures = Signatures(map![
+            &env,
+            (
+                limited_key.clone(),
+                Signature::Ed25519(BytesN::from_array(&env, &signature))
+            )
+        ]);
+        let contexts = vec![
+            &env,
+            Context::Contract(ContractContext {
+                contract: wallet_address.clone(),
+                fn_name: Symbol::new(&env, fn_name),
+                args,
+            }),
+        ];
+
+        env.try_invoke_contract_check_auth::<Error>(
+            &wallet_address,
+            &payload,
+            signatures.into_val(&env),
+            &contexts,
+        )
+    };
+
+    // It can't veto a recovery, touch the guardians or add signers itself
+    assert_eq!(
+        check("cancel_recovery", vec![&env, ().into_val(&env)]),
+        Err(Ok(Error::MissingContext))
+    );
+    assert_eq!(
+        check(
+            "set_recovery",
+            vec![&env, guardians.into_val(&env), 1u32.into_val(&env), 0u32.into_val(&env)]
+        ),
+        Err(Ok(Error::MissingContext))
+    );
+    assert_eq!(
+        check(
+            "add_signer",
+            vec![&env, ed25519_signer(&env, &keypair(4), SignerLimits(None)).into_val(&env)]
+        ),
+        Err(Ok(Error::MissingContext))
+    );
+}
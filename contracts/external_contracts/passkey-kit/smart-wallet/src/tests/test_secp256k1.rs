@@ -0,0 +1,175 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{Contract, ContractClient};
+use k256::ecdsa::SigningKey;
+use smart_wallet_interface::{
+    secp256k1::Secp256k1Error,
+    types::{
+        Error, Secp256k1Signature, Signature, Signatures, Signer, SignerExpiration, SignerKey,
+        SignerLimits, SignerStorage,
+    },
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    map, symbol_short,
+    testutils::Address as _,
+    vec, Address, Bytes, BytesN, Env, IntoVal, InvokeError,
+};
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+}
+
+// The 65-byte uncompressed public key
+fn public_key(env: &Env, key: &SigningKey) -> Bytes {
+    Bytes::from_slice(env, key.verifying_key().to_encoded_point(false).as_bytes())
+}
+
+// The 20-byte Ethereum address
+fn eth_address(env: &Env, key: &SigningKey) -> Bytes {
+    let public_key = public_key(env, key);
+    let hash = env.crypto().keccak256(&public_key.slice(1..));
+
+    Bytes::from_slice(env, &hash.to_array()[12..])
+}
+
+fn secp256k1_signer(key: Bytes) -> Signer {
+    Signer::Secp256k1(
+        key,
+        SignerExpiration(None),
+        SignerLimits(None),
+        SignerStorage::Persistent,
+    )
+}
+
+// Sign the payload as `personal_sign` would, with an Ethereum-style recovery id
+fn sign(env: &Env, key: &SigningKey, payload: &BytesN<32>) -> Signature {
+    let mut message = Bytes::from_slice(env, b"\x19Ethereum Signed Message:\n32");
+
+    message.extend_from_array(&payload.to_array());
+
+    let digest = env.crypto().keccak256(&message);
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&digest.to_array()).unwrap();
+
+    Signature::Secp256k1(Secp256k1Signature {
+        signature: BytesN::from_array(env, &signature.to_bytes().into()),
+        recovery_id: 27 + recovery_id.to_byte() as u32,
+    })
+}
+
+fn check(
+    env: &Env,
+    wallet_address: &Address,
+    signer_key: SignerKey,
+    signature: Signature,
+) -> Result<(), Result<Error, InvokeError>> {
+    let payload = BytesN::from_array(env, &[7; 32]);
+    let contexts = vec![
+        env,
+        Context::Contract(ContractContext {
+            contract: Address::generate(env),
+            fn_name: symbol_short!("transfer"),
+            args: vec![env, wallet_address.into_val(env)],
+        }),
+    ];
+
+    env.try_invoke_contract_check_auth::<Error>(
+        wallet_address,
+        &payload,
+        Signatures(map![env, (signer_key, signature)]).into_val(env),
+        &contexts,
+    )
+}
+
+#[test]
+fn test_secp256k1_signers() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let alice = signing_key(1);
+    let bob = signing_key(2);
+    let payload = BytesN::from_array(&env, &[7; 32]);
+
+    // Alice is registered by public key, Bob by Ethereum address
+    let wallet_address = env.register(Contract, (secp256k1_signer(public_key(&env, &alice)),));
+    let wallet_client = ContractClient::new(&env, &wallet_address);
+
+    wallet_client.add_signer(&secp256k1_signer(eth_address(&env, &bob)));
+
+    let alice_key = SignerKey::Secp256k1(public_key(&env, &alice));
+    let bob_key = SignerKey::Secp256k1(eth_address(&env, &bob));
+
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            alice_key.clone(),
+            sign(&env, &alice, &payload)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            bob_key.clone(),
+            sign(&env, &bob, &payload)
+        ),
+        Ok(())
+    );
+
+    // Signatures from the wrong key, or over a different payload, don't recover to the signer
+    assert_eq!(
+        check(&env, &wallet_address, alice_key, sign(&env, &bob, &payload)),
+        Err(Err(InvokeError::Contract(
+            Secp256k1Error::SignatureMismatch as u32
+        )))
+    );
+    assert_eq!(
+        check(
+            &env,
+            &wallet_address,
+            bob_key,
+            sign(&env, &bob, &BytesN::from_array(&env, &[8; 32]))
+        ),
+        Err(Err(InvokeError::Contract(
+            Secp256k1Error::SignatureMismatch as u32
+        )))
+    );
+
+    assert_eq!(
+        wallet_client.list_signers(),
+        vec![
+            &env,
+            secp256k1_signer(public_key(&env, &alice)),
+            secp256k1_signer(eth_address(&env, &bob))
+        ]
+    );
+}
+
+#[test]
+fn test_secp256k1_invalid_key() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let wallet_address = env.register(
+        Contract,
+        (secp256k1_signer(public_key(&env, &signing_key(1))),),
+    );
+    let wallet_client = ContractClient::new(&env, &wallet_address);
+
+    // Neither a 20-byte address nor an uncompressed 65-byte key
+    for key in [
+        Bytes::from_array(&env, &[1; 33]),
+        Bytes::from_array(&env, &[1; 65]),
+    ] {
+        assert_eq!(
+            wallet_client.try_add_signer(&secp256k1_signer(key)),
+            Err(Ok(Secp256k1Error::InvalidKey.into()))
+        );
+    }
+}
@@ -0,0 +1,127 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{Contract, ContractClient};
+use smart_wallet_interface::types::{
+    Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage,
+};
+use soroban_sdk::{bytes, map, testutils::Address as _, vec, Address, BytesN, Env};
+
+fn ed25519_signer(env: &Env, seed: u8, signer_storage: SignerStorage) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(env, &[seed; 32]),
+        SignerExpiration(None),
+        SignerLimits(None),
+        signer_storage,
+    )
+}
+
+#[test]
+fn test_list_signers() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let admin = ed25519_signer(&env, 1, SignerStorage::Persistent);
+    let wallet_address = env.register(Contract, (admin.clone(),));
+    let wallet_client = ContractClient::new(&env, &wallet_address);
+
+    let policy = Signer::Policy(
+        Address::generate(&env),
+        SignerExpiration(Some(1_000)),
+        SignerLimits(Some(map![&env, (Address::generate(&env), None)])),
+        SignerStorage::Persistent,
+    );
+    let passkey = Signer::Secp256r1(
+        bytes!(&env, 0x0102),
+        BytesN::from_array(&env, &[4; 65]),
+        SignerExpiration(None),
+        SignerLimits(None),
+        SignerStorage::Temporary,
+    );
+    // Limited, so moving it between storage classes isn't timelocked
+    let session_limits = SignerLimits(Some(map![&env, (Address::generate(&env), None)]));
+    let session = Signer::Ed25519(
+        BytesN::from_array(&env, &[2; 32]),
+        SignerExpiration(None),
+        session_limits.clone(),
+        SignerStorage::Temporary,
+    );
+
+    wallet_client.add_signer(&policy);
+    wallet_client.add_signer(&passkey);
+    wallet_client.add_signer(&session);
+
+    assert_eq!(
+        wallet_client.list_signers(),
+        vec![
+            &env,
+            admin.clone(),
+            policy.clone(),
+            passkey.clone(),
+            session.clone()
+        ]
+    );
+
+    // Updates show the new storage class in place
+    let session = Signer::Ed25519(
+        BytesN::from_array(&env, &[2; 32]),
+        SignerExpiration(None),
+        session_limits,
+        SignerStorage::Persistent,
+    );
+
+    wallet_client.update_signer(&session);
+    wallet_client.remove_signer(&SignerKey::Policy(match &policy {
+        Signer::Policy(address, ..) => address.clone(),
+        _ => unreachable!(),
+    }));
+
+    assert_eq!(
+        wallet_client.list_signers(),
+        vec![&env, admin.clone(), passkey.clone(), session.clone()]
+    );
+}
+
+#[test]
+fn test_list_signers_drops_expired_temporary_entries() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+
+    let admin = ed25519_signer(&env, 1, SignerStorage::Persistent);
+    let wallet_address = env.register(Contract, (admin.clone(),));
+    let wallet_client = ContractClient::new(&env, &wallet_address);
+    let session = ed25519_signer(&env, 2, SignerStorage::Temporary);
+    let session_key = SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32]));
+
+    wallet_client.add_signer(&session);
+
+    // Stand-in for the temporary entry's TTL running out
+    env.as_contract(&wallet_address, || {
+        env.storage().temporary().remove(&session_key);
+    });
+
+    // The stale key weighs on the index only, never on the instance every call loads
+    let (in_instance, indexed) = env.as_contract(&wallet_address, || {
+        let index = crate::signer::SIGNERS;
+        (
+            env.storage().instance().has(&index),
+            env.storage()
+                .persistent()
+                .get::<_, soroban_sdk::Vec<SignerKey>>(&index)
+                .unwrap()
+                .len(),
+        )
+    });
+
+    assert!(!in_instance);
+    assert_eq!(indexed, 2);
+    assert_eq!(wallet_client.list_signers(), vec![&env, admin.clone()]);
+
+    // Re-adding the same key brings it back
+    wallet_client.add_signer(&session);
+
+    assert_eq!(wallet_client.list_signers(), vec![&env, admin, session]);
+}
@@ -0,0 +1,396 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{Contract, ContractClient};
+use smart_wallet_interface::{
+    types::{Error, Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage},
+    upgrade::{PendingRemoval, PendingUpgrade, SignerChange, UpgradeError, UPGRADE_DELAY},
+};
+use soroban_sdk::{
+    map, symbol_short,
+    testutils::{Address as _, Events, Ledger},
+    xdr::{Limits, ScEnvMetaEntry, ScEnvMetaEntryInterfaceVersion, WriteXdr},
+    Address, Bytes, BytesN, Env, IntoVal, TryFromVal,
+};
+
+fn setup(env: &Env) -> ContractClient<'_> {
+    env.mock_all_auths();
+
+    let wallet_address = env.register(
+        Contract,
+        (Signer::Ed25519(
+            BytesN::from_array(env, &[1; 32]),
+            SignerExpiration(None),
+            SignerLimits(None),
+            SignerStorage::Persistent,
+        ),),
+    );
+
+    ContractClient::new(env, &wallet_address)
+}
+
+// The smallest module the host accepts as contract code: just its env meta
+fn empty_contract_wasm() -> std::vec::Vec<u8> {
+    let meta = ScEnvMetaEntry::ScEnvMetaKindInterfaceVersion(ScEnvMetaEntryInterfaceVersion {
+        protocol: 22,
+        pre_release: 0,
+    })
+    .to_xdr(Limits::none())
+    .unwrap();
+    let name = b"contractenvmetav0";
+    let mut wasm = std::vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+
+    wasm.push(0); // custom section
+    wasm.push((1 + name.len() + meta.len()) as u8);
+    wasm.push(name.len() as u8);
+    wasm.extend_from_slice(name);
+    wasm.extend_from_slice(&meta);
+
+    wasm
+}
+
+fn empty_contract_wasm_hash(env: &Env) -> BytesN<32> {
+    env.crypto()
+        .sha256(&Bytes::from_slice(env, &empty_contract_wasm()))
+        .into()
+}
+
+#[test]
+fn test_upgrade_timelock() {
+    let env = Env::default();
+    let wallet_client = setup(&env);
+    let hash = empty_contract_wasm_hash(&env);
+    let earliest_ledger = env.ledger().sequence() + UPGRADE_DELAY;
+
+    assert_eq!(
+        wallet_client.try_schedule_upgrade(&hash, &(earliest_ledger - 1)),
+        Err(Ok(UpgradeError::TooEarly.into()))
+    );
+
+    wallet_client.schedule_upgrade(&hash, &earliest_ledger);
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("upgrade"),
+            symbol_short!("schedule")
+        )
+            .into_val(&env)
+    );
+    assert_eq!(
+        wallet_client.pending_upgrade(),
+        Some(PendingUpgrade {
+            hash: hash.clone(),
+            earliest_ledger,
+        })
+    );
+    assert_eq!(
+        wallet_client.try_update_contract_code(&hash),
+        Err(Ok(UpgradeError::TooEarly.into()))
+    );
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number = earliest_ledger);
+
+    assert_eq!(
+        wallet_client.try_update_contract_code(&BytesN::from_array(&env, &[0; 32])),
+        Err(Ok(UpgradeError::HashMismatch.into()))
+    );
+
+    // Uploaded only now so the code entry is still live after the wait
+    env.deployer()
+        .upload_contract_wasm(Bytes::from_slice(&env, &empty_contract_wasm()));
+    wallet_client.update_contract_code(&hash);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("upgrade"),
+            symbol_short!("execute")
+        )
+            .into_val(&env)
+    );
+}
+
+#[test]
+fn test_upgrade_cancel() {
+    let env = Env::default();
+    let wallet_client = setup(&env);
+    let hash = BytesN::from_array(&env, &[9; 32]);
+    let earliest_ledger = env.ledger().sequence() + UPGRADE_DELAY;
+
+    assert_eq!(
+        wallet_client.try_update_contract_code(&hash),
+        Err(Ok(UpgradeError::NoUpgrade.into()))
+    );
+    assert_eq!(
+        wallet_client.try_cancel_upgrade(),
+        Err(Ok(UpgradeError::NoUpgrade.into()))
+    );
+
+    wallet_client.schedule_upgrade(&hash, &earliest_ledger);
+    wallet_client.cancel_upgrade();
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("upgrade"),
+            symbol_short!("cancel")
+        )
+            .into_val(&env)
+    );
+    assert_eq!(wallet_client.pending_upgrade(), None);
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number = earliest_ledger);
+
+    assert_eq!(
+        wallet_client.try_update_contract_code(&hash),
+        Err(Ok(UpgradeError::NoUpgrade.into()))
+    );
+}
+
+fn ed25519_signer(env: &Env, seed: u8, signer_limits: SignerLimits) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(env, &[seed; 32]),
+        SignerExpiration(None),
+        signer_limits,
+        SignerStorage::Persistent,
+    )
+}
+
+#[test]
+fn test_removal_timelock() {
+    let env = Env::default();
+    let wallet_client = setup(&env);
+    let signer_key = SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32]));
+    let earliest_ledger = env.ledger().sequence() + UPGRADE_DELAY;
+
+    wallet_client.add_signer(&ed25519_signer(&env, 2, SignerLimits(None)));
+
+    // An unlimited signer can cancel upgrades, so its removal is only scheduled
+    wallet_client.remove_signer(&signer_key);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("removal"),
+            symbol_short!("schedule")
+        )
+            .into_val(&env)
+    );
+    let pending = PendingRemoval {
+        earliest_ledger,
+        change: SignerChange::Remove,
+    };
+
+    assert_eq!(
+        <(SignerKey, PendingRemoval)>::try_from_val(&env, &data),
+        Ok((signer_key.clone(), pending.clone()))
+    );
+    assert_eq!(wallet_client.pending_removal(&signer_key), Some(pending));
+    assert_eq!(
+        wallet_client.try_add_signer(&ed25519_signer(&env, 2, SignerLimits(None))),
+        Err(Ok(Error::AlreadyExists.into()))
+    );
+    assert_eq!(
+        wallet_client.try_remove_signer(&signer_key),
+        Err(Ok(UpgradeError::TooEarly.into()))
+    );
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number = earliest_ledger);
+
+    wallet_client.remove_signer(&signer_key);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("remove"),
+            signer_key.clone()
+        )
+            .into_val(&env)
+    );
+    assert_eq!(wallet_client.pending_removal(&signer_key), None);
+
+    // Limited signers never could cancel, so they go straight away
+    let limited = ed25519_signer(
+        &env,
+        3,
+        SignerLimits(Some(map![&env, (Address::generate(&env), None)])),
+    );
+
+    let signer_key = SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32]));
+
+    wallet_client.add_signer(&limited);
+    wallet_client.remove_signer(&signer_key);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (symbol_short!("sw_v1"), symbol_short!("remove"), signer_key).into_val(&env)
+    );
+}
+
+#[test]
+fn test_removal_cancel() {
+    let env = Env::default();
+    let wallet_client = setup(&env);
+    let signer_key = SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32]));
+    let limited = ed25519_signer(
+        &env,
+        1,
+        SignerLimits(Some(map![&env, (Address::generate(&env), None)])),
+    );
+
+    assert_eq!(
+        wallet_client.try_cancel_removal(&signer_key),
+        Err(Ok(UpgradeError::NoRemoval.into()))
+    );
+
+    // Limiting an unlimited signer waits out the same delay
+    wallet_client.update_signer(&limited);
+    wallet_client.cancel_removal(&signer_key);
+
+    assert_eq!(env.auths()[0].0, wallet_client.address);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("removal"),
+            symbol_short!("cancel")
+        )
+            .into_val(&env)
+    );
+    assert_eq!(wallet_client.pending_removal(&signer_key), None);
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += UPGRADE_DELAY);
+
+    // Cancelled, so the next update starts the wait over
+    wallet_client.update_signer(&limited);
+
+    assert_eq!(
+        wallet_client.pending_removal(&signer_key),
+        Some(PendingRemoval {
+            earliest_ledger: env.ledger().sequence() + UPGRADE_DELAY,
+            change: SignerChange::Update(limited),
+        })
+    );
+
+    // Only the scheduled change goes ahead
+    assert_eq!(
+        wallet_client.try_remove_signer(&signer_key),
+        Err(Ok(UpgradeError::RemovalMismatch.into()))
+    );
+}
+
+fn secp256r1_signer(env: &Env, seed: u8, signer_expiration: SignerExpiration) -> Signer {
+    let mut public_key = [seed; 65];
+
+    public_key[0] = 0x04;
+
+    Signer::Secp256r1(
+        Bytes::from_array(env, &[1; 16]),
+        BytesN::from_array(env, &public_key),
+        signer_expiration,
+        SignerLimits(None),
+        SignerStorage::Persistent,
+    )
+}
+
+#[test]
+fn test_key_swap_timelock() {
+    let env = Env::default();
+    let wallet_client = setup(&env);
+    let signer_key = SignerKey::Secp256r1(Bytes::from_array(&env, &[1; 16]));
+    let expiring = env.ledger().sequence() + UPGRADE_DELAY * 2;
+
+    wallet_client.add_signer(&secp256r1_signer(&env, 1, SignerExpiration(Some(expiring))));
+
+    // Extending an unlimited signer changes nothing else, so it goes straight away
+    wallet_client.update_signer(&secp256r1_signer(&env, 1, SignerExpiration(None)));
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("update"),
+            signer_key.clone()
+        )
+            .into_val(&env)
+    );
+
+    // Swapping its public key under the same credential id waits, with an event
+    let swapped = secp256r1_signer(&env, 2, SignerExpiration(None));
+
+    wallet_client.update_signer(&swapped);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("removal"),
+            symbol_short!("schedule")
+        )
+            .into_val(&env)
+    );
+    assert_eq!(
+        wallet_client.pending_removal(&signer_key),
+        Some(PendingRemoval {
+            earliest_ledger: env.ledger().sequence() + UPGRADE_DELAY,
+            change: SignerChange::Update(swapped.clone()),
+        })
+    );
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += UPGRADE_DELAY);
+
+    assert_eq!(
+        wallet_client.try_update_signer(&secp256r1_signer(&env, 3, SignerExpiration(None))),
+        Err(Ok(UpgradeError::RemovalMismatch.into()))
+    );
+
+    wallet_client.update_signer(&swapped);
+
+    let (_, topics, _) = env.events().all().last().unwrap();
+
+    assert_eq!(
+        topics,
+        (
+            symbol_short!("sw_v1"),
+            symbol_short!("update"),
+            signer_key.clone()
+        )
+            .into_val(&env)
+    );
+    assert_eq!(wallet_client.pending_removal(&signer_key), None);
+}
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracterror, contracttype, Address, Bytes, BytesN, Map, Vec};
+use soroban_sdk::{contracterror, contracttype, Address, Bytes, BytesN, Map, Symbol, Vec};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -46,6 +46,8 @@ pub enum Signer {
         SignerLimits,
         SignerStorage,
     ),
+    // 65-byte uncompressed public key or 20-byte Ethereum address
+    Secp256k1(Bytes, SignerExpiration, SignerLimits, SignerStorage),
 }
 
 #[contracttype]
@@ -54,6 +56,9 @@ pub enum SignerKey {
     Policy(Address),
     Ed25519(BytesN<32>),
     Secp256r1(Bytes),
+    Secp256k1(Bytes),
+    // Only valid inside SignerLimits: met once the group's threshold of members have signed
+    Group(Symbol),
 }
 
 #[contracttype]
@@ -62,6 +67,7 @@ pub enum SignerVal {
     Policy(SignerExpiration, SignerLimits),
     Ed25519(SignerExpiration, SignerLimits),
     Secp256r1(BytesN<65>, SignerExpiration, SignerLimits),
+    Secp256k1(SignerExpiration, SignerLimits),
 }
 
 #[contracttype]
@@ -72,12 +78,20 @@ pub struct Secp256r1Signature {
     pub signature: BytesN<64>,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Secp256k1Signature {
+    pub signature: BytesN<64>,
+    pub recovery_id: u32, // 0/1, or Ethereum's 27/28
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum Signature {
     Policy,
     Ed25519(BytesN<64>),
     Secp256r1(Secp256r1Signature),
+    Secp256k1(Secp256k1Signature),
 }
 
 #[contracttype]
@@ -1,11 +1,35 @@
-use smart_wallet_interface::types::{Error, Secp256r1Signature};
-use soroban_sdk::{crypto::Hash, panic_with_error, BytesN, Env};
+use smart_wallet_interface::{
+    base64_url, client_data,
+    types::{Error, Secp256r1Signature},
+};
+use soroban_sdk::{
+    contracterror, crypto::Hash, panic_with_error, symbol_short, Bytes, BytesN, Env, Symbol, Vec,
+};
 
-use crate::base64_url;
+const ORIGINS: Symbol = symbol_short!("origins");
 
-#[derive(serde::Deserialize)]
-struct ClientDataJson<'a> {
-    challenge: &'a str,
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum ClientDataError {
+    InvalidType = 50,
+    OriginNotAllowed = 51,
+}
+
+pub fn get_origins(env: &Env) -> Vec<Bytes> {
+    env.storage()
+        .instance()
+        .get::<Symbol, Vec<Bytes>>(&ORIGINS)
+        .unwrap_or(Vec::new(env))
+}
+
+// An empty list accepts any origin
+pub fn set_origins(env: &Env, origins: &Vec<Bytes>) {
+    if origins.is_empty() {
+        env.storage().instance().remove(&ORIGINS);
+    } else {
+        env.storage().instance().set(&ORIGINS, origins);
+    }
 }
 
 pub fn verify_secp256r1_signature(
@@ -28,12 +52,12 @@ pub fn verify_secp256r1_signature(
         &signature,
     );
 
-    // Parse the client data JSON, extracting the base64 url encoded challenge.
-    let client_data_json = client_data_json.to_buffer::<1024>(); // <- TODO why 1024?
-    let client_data_json = client_data_json.as_slice();
-    let (client_data_json, _): (ClientDataJson, _) =
-        serde_json_core::de::from_slice(client_data_json)
-            .unwrap_or_else(|_| panic_with_error!(env, Error::JsonParseError));
+    // Parse the client data JSON, extracting the base64 url encoded challenge, type and origin.
+    let client_data = client_data::parse(&client_data_json)
+        .unwrap_or_else(|| panic_with_error!(env, Error::JsonParseError));
+    let challenge = client_data
+        .challenge
+        .unwrap_or_else(|| panic_with_error!(env, Error::JsonParseError));
 
     // Build what the base64 url challenge is expecting.
     let mut expected_challenge = [0u8; 43];
@@ -42,7 +66,21 @@ pub fn verify_secp256r1_signature(
 
     // Check that the challenge inside the client data JSON that was signed is identical to the expected challenge.
     // TODO is this check actually necessary or is the secp256r1_verify sufficient?
-    if client_data_json.challenge.as_bytes() != expected_challenge {
+    if challenge != Bytes::from_array(env, &expected_challenge) {
         panic_with_error!(env, Error::ClientDataJsonChallengeIncorrect)
     }
-}
\ No newline at end of file
+
+    if client_data.type_ != Some(Bytes::from_slice(env, b"webauthn.get")) {
+        panic_with_error!(env, ClientDataError::InvalidType)
+    }
+
+    let origins = get_origins(env);
+
+    if !origins.is_empty()
+        && !client_data
+            .origin
+            .is_some_and(|origin| origins.contains(&origin))
+    {
+        panic_with_error!(env, ClientDataError::OriginNotAllowed)
+    }
+}
@@ -1,6 +1,8 @@
 #![no_std]
+use smart_wallet_interface::types::{Signer, SignerExpiration, SignerLimits, SignerStorage};
 use soroban_sdk::{
-    contract, contracterror, contractimpl, symbol_short, vec, Address, BytesN, Env, Symbol,
+    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Bytes, BytesN, Env,
+    Symbol,
 };
 
 #[contract]
@@ -10,13 +12,25 @@ pub struct Contract;
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Error {
     NotInited = 1,
-    AlreadyInited = 2,
+    AlreadyRegistered = 3,
 }
 
-const STORAGE_KEY_WASM_HASH: Symbol = symbol_short!("hash");
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Wallet(Bytes), // credential id -> smart wallet address
+}
+
+const STORAGE_KEY_WALLET_WASM_HASH: Symbol = symbol_short!("w_hash");
 
 #[contractimpl]
 impl Contract {
+    /// Fixes the smart wallet code `deploy_wallet` deploys, at deploy time.
+    pub fn __constructor(env: Env, wallet_wasm_hash: BytesN<32>) {
+        env.storage()
+            .instance()
+            .set(&STORAGE_KEY_WALLET_WASM_HASH, &wallet_wasm_hash);
+    }
     pub fn extend_ttl(env: Env) {
         let max_ttl = env.storage().max_ttl();
         let contract_address = env.current_contract_address();
@@ -29,31 +43,68 @@ impl Contract {
         env.deployer()
             .extend_ttl_for_contract_instance(contract_address.clone(), max_ttl, max_ttl);
     }
-    pub fn init(env: Env, wasm_hash: BytesN<32>) -> Result<(), Error> {
-        if env.storage().instance().has(&STORAGE_KEY_WASM_HASH) {
-            return Err(Error::AlreadyInited);
+    /// Deploys a passkey-kit smart wallet with the passkey as its signer, passed to the
+    /// constructor so there's no separate init call to front-run. Each credential id gets
+    /// one wallet: once registered, a deploy under the same id is refused, so nobody can
+    /// point a passkey's registry entry at a key of their own afterwards.
+    pub fn deploy_wallet(
+        env: Env,
+        credential_id: Bytes,
+        public_key: BytesN<65>,
+    ) -> Result<Address, Error> {
+        let wasm_hash = env
+            .storage()
+            .instance()
+            .get::<Symbol, BytesN<32>>(&STORAGE_KEY_WALLET_WASM_HASH)
+            .ok_or(Error::NotInited)?;
+
+        let key = DataKey::Wallet(credential_id.clone());
+
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyRegistered);
         }
 
+        let salt = env.crypto().sha256(&credential_id).to_bytes();
+        let signer = Signer::Secp256r1(
+            credential_id,
+            public_key,
+            SignerExpiration(None),
+            SignerLimits(None),
+            SignerStorage::Persistent,
+        );
+        let address = env
+            .deployer()
+            .with_current_contract(salt)
+            .deploy_v2(wasm_hash, (signer,));
+
+        env.storage().persistent().set(&key, &address);
+
+        let max_ttl = env.storage().max_ttl();
+
         env.storage()
-            .instance()
-            .set(&STORAGE_KEY_WASM_HASH, &wasm_hash);
+            .persistent()
+            .extend_ttl(&key, max_ttl, max_ttl);
 
         Self::extend_ttl(env);
 
-        Ok(())
+        Ok(address)
     }
-    pub fn deploy(env: Env, salt: BytesN<32>, pk: BytesN<65>) -> Result<Address, Error> {
-        let wasm_hash = env
-            .storage()
-            .instance()
-            .get::<Symbol, BytesN<32>>(&STORAGE_KEY_WASM_HASH)
-            .ok_or(Error::NotInited)?;
+    /// The smart wallet deployed for a passkey, e.g. to find it again from a new device,
+    /// where an assertion only gives the credential id.
+    pub fn get_wallet(env: Env, credential_id: Bytes) -> Option<Address> {
+        let key = DataKey::Wallet(credential_id);
+        let address = env.storage().persistent().get::<DataKey, Address>(&key);
 
-        let address = env.deployer().with_current_contract(salt).deploy(wasm_hash);
-        let () = env.invoke_contract(&address, &symbol_short!("init"), vec![&env, pk.to_val()]);
+        if address.is_some() {
+            let max_ttl = env.storage().max_ttl();
 
-        Self::extend_ttl(env);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, max_ttl, max_ttl);
+        }
 
-        Ok(address)
+        address
     }
-}
\ No newline at end of file
+}
+
+mod test;
@@ -0,0 +1,82 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::{Contract, ContractClient, Error};
+use smart_wallet_interface::types::{SignerKey, SignerVal};
+use soroban_sdk::{bytes, Bytes, BytesN, Env};
+
+// Only its code is needed, the wallet's own interface is never called here
+const SMART_WALLET_WASM: &[u8] =
+    include_bytes!("../../../../target/wasm32v1-none/release/smart_wallet.wasm");
+
+fn create_factory<'a>(e: &Env) -> ContractClient<'a> {
+    let wallet_wasm_hash = e.deployer().upload_contract_wasm(SMART_WALLET_WASM);
+    let factory_address = e.register(Contract, (wallet_wasm_hash,));
+
+    ContractClient::new(e, &factory_address)
+}
+
+fn public_key(e: &Env, seed: u8) -> BytesN<65> {
+    let mut key = [seed; 65];
+
+    key[0] = 0x04;
+
+    BytesN::from_array(e, &key)
+}
+
+#[test]
+fn test_deploy_wallet() {
+    let e = Env::default();
+    let factory = create_factory(&e);
+    let credential_id: Bytes = bytes!(&e, 0x0102);
+    let pk = public_key(&e, 1);
+
+    assert_eq!(factory.get_wallet(&credential_id), None);
+
+    // No auth needed: whoever pays, the passkey alone controls the wallet
+    let wallet = factory.deploy_wallet(&credential_id, &pk);
+
+    assert!(e.auths().is_empty());
+    assert_eq!(factory.get_wallet(&credential_id), Some(wallet.clone()));
+
+    // The constructor stored the passkey as an unlimited, persistent signer
+    let signer_val = e.as_contract(&wallet, || {
+        e.storage()
+            .persistent()
+            .get::<SignerKey, SignerVal>(&SignerKey::Secp256r1(credential_id.clone()))
+    });
+
+    assert!(matches!(
+        signer_val,
+        Some(SignerVal::Secp256r1(key, expiration, limits))
+            if key == pk && expiration.0.is_none() && limits.0.is_none()
+    ));
+    assert_eq!(
+        factory.try_deploy_wallet(&credential_id, &pk),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+}
+
+#[test]
+fn test_credential_id_cannot_be_squatted() {
+    let e = Env::default();
+    let factory = create_factory(&e);
+    let credential_id: Bytes = bytes!(&e, 0x0102);
+    let (pk, other_pk) = (public_key(&e, 1), public_key(&e, 2));
+
+    let wallet = factory.deploy_wallet(&credential_id, &pk);
+
+    // Someone else's key can't take over a registered credential id
+    assert_eq!(
+        factory.try_deploy_wallet(&credential_id, &other_pk),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+    assert_eq!(factory.get_wallet(&credential_id), Some(wallet));
+
+    // Other credential ids are free as before
+    let other_id: Bytes = bytes!(&e, 0x0304);
+    let other_wallet = factory.deploy_wallet(&other_id, &other_pk);
+
+    assert_ne!(other_wallet, factory.get_wallet(&credential_id).unwrap());
+    assert_eq!(factory.get_wallet(&other_id), Some(other_wallet));
+}
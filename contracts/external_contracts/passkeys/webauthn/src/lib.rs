@@ -1,12 +1,13 @@
 #![no_std]
+use smart_wallet_interface::{base64_url, client_data};
 use soroban_sdk::{
     auth::{Context, CustomAccountInterface},
     contract, contracterror, contractimpl, contracttype,
     crypto::Hash,
-    symbol_short, Bytes, BytesN, Env, Symbol, Vec,
+    symbol_short, Bytes, BytesN, Env, Map, Symbol, TryFromVal, Val, Vec,
 };
 
-mod base64_url;
+mod test;
 
 #[contract]
 pub struct Contract;
@@ -21,9 +22,19 @@ pub enum Error {
     Secp256r1SignatureParse = 5,
     Secp256r1VerifyFailed = 6,
     JsonParseError = 7,
+    CredentialExists = 8,
+    CredentialNotFound = 9,
+    LastCredential = 10,
+    OriginNotAllowed = 11,
+    InvalidType = 12,
 }
 
+// The original single key set by `init`, used by signatures without a credential id
 const STORAGE_KEY_PK: Symbol = symbol_short!("pk");
+// Additional passkeys, credential id -> public key
+const STORAGE_KEY_CREDENTIALS: Symbol = symbol_short!("creds");
+// clientDataJSON origins signatures must come from, any origin while empty
+const STORAGE_KEY_ORIGINS: Symbol = symbol_short!("origins");
 
 #[contractimpl]
 impl Contract {
@@ -50,8 +61,93 @@ impl Contract {
 
         Ok(())
     }
+    /// Registers another passkey, e.g. a new device. Needs a signature from an existing one.
+    pub fn add_credential(env: Env, id: Bytes, pk: BytesN<65>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        let mut credentials = get_credentials(&env);
+
+        if credentials.contains_key(id.clone()) {
+            return Err(Error::CredentialExists);
+        }
+
+        credentials.set(id, pk);
+        env.storage()
+            .instance()
+            .set(&STORAGE_KEY_CREDENTIALS, &credentials);
+
+        Self::extend_ttl(env);
+
+        Ok(())
+    }
+    /// Removes a passkey, or the legacy `init` key when `id` is `None`. The last key can't be removed.
+    pub fn remove_credential(env: Env, id: Option<Bytes>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        let mut credentials = get_credentials(&env);
+        let has_legacy = env.storage().instance().has(&STORAGE_KEY_PK);
+
+        match id {
+            None if has_legacy => {
+                if credentials.is_empty() {
+                    return Err(Error::LastCredential);
+                }
+
+                env.storage().instance().remove(&STORAGE_KEY_PK);
+            }
+            Some(id) if credentials.contains_key(id.clone()) => {
+                if credentials.len() == 1 && !has_legacy {
+                    return Err(Error::LastCredential);
+                }
+
+                credentials.remove(id);
+                env.storage()
+                    .instance()
+                    .set(&STORAGE_KEY_CREDENTIALS, &credentials);
+            }
+            _ => return Err(Error::CredentialNotFound),
+        }
+
+        Self::extend_ttl(env);
+
+        Ok(())
+    }
+    pub fn credentials(env: Env) -> Map<Bytes, BytesN<65>> {
+        get_credentials(&env)
+    }
+    /// Limits signatures to these origins, e.g. the web app and its Android app's
+    /// `android:apk-key-hash:` origin. An empty list allows any origin.
+    pub fn set_origins(env: Env, origins: Vec<Bytes>) {
+        env.current_contract_address().require_auth();
+
+        if origins.is_empty() {
+            env.storage().instance().remove(&STORAGE_KEY_ORIGINS);
+        } else {
+            env.storage().instance().set(&STORAGE_KEY_ORIGINS, &origins);
+        }
+
+        Self::extend_ttl(env);
+    }
+    pub fn origins(env: Env) -> Vec<Bytes> {
+        get_origins(&env)
+    }
+}
+
+fn get_origins(env: &Env) -> Vec<Bytes> {
+    env.storage()
+        .instance()
+        .get(&STORAGE_KEY_ORIGINS)
+        .unwrap_or(Vec::new(env))
 }
 
+fn get_credentials(env: &Env) -> Map<Bytes, BytesN<65>> {
+    env.storage()
+        .instance()
+        .get(&STORAGE_KEY_CREDENTIALS)
+        .unwrap_or(Map::new(env))
+}
+
+/// The original signature, made with the legacy `init` key.
 #[contracttype]
 pub struct Signature {
     pub authenticator_data: Bytes,
@@ -59,30 +155,65 @@ pub struct Signature {
     pub signature: BytesN<64>,
 }
 
-#[derive(serde::Deserialize)]
-struct ClientDataJson<'a> {
-    challenge: &'a str,
+/// A signature made with one of the passkeys added by `add_credential`.
+#[contracttype]
+pub struct CredentialSignature {
+    pub id: Bytes,
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    pub signature: BytesN<64>,
 }
 
 #[contractimpl]
 impl CustomAccountInterface for Contract {
     type Error = Error;
-    type Signature = Signature;
+    // Either a `Signature` or a `CredentialSignature`, so clients that predate
+    // credential ids keep working unchanged
+    type Signature = Val;
 
     #[allow(non_snake_case)]
     fn __check_auth(
         env: Env,
         signature_payload: Hash<32>,
-        signature: Signature,
+        signature: Val,
         _auth_contexts: Vec<Context>,
     ) -> Result<(), Error> {
-        // Verify that the public key produced the signature.
-        let pk = env
-            .storage()
-            .instance()
-            .get(&STORAGE_KEY_PK)
-            .ok_or(Error::NotInited)?;
+        // Only a `CredentialSignature` has an `id` field
+        let fields = Map::<Symbol, Val>::try_from_val(&env, &signature)
+            .map_err(|_| Error::Secp256r1SignatureParse)?;
+        let (pk, signature): (BytesN<65>, Signature) = if fields.contains_key(symbol_short!("id")) {
+            let CredentialSignature {
+                id,
+                authenticator_data,
+                client_data_json,
+                signature,
+            } = CredentialSignature::try_from_val(&env, &signature)
+                .map_err(|_| Error::Secp256r1SignatureParse)?;
+            let pk = get_credentials(&env)
+                .get(id)
+                .ok_or(Error::CredentialNotFound)?;
 
+            (
+                pk,
+                Signature {
+                    authenticator_data,
+                    client_data_json,
+                    signature,
+                },
+            )
+        } else {
+            let signature = Signature::try_from_val(&env, &signature)
+                .map_err(|_| Error::Secp256r1SignatureParse)?;
+            let pk = env
+                .storage()
+                .instance()
+                .get(&STORAGE_KEY_PK)
+                .ok_or(Error::NotInited)?;
+
+            (pk, signature)
+        };
+
+        // Verify that the public key produced the signature.
         let mut payload = Bytes::new(&env);
 
         payload.append(&signature.authenticator_data);
@@ -93,11 +224,10 @@ impl CustomAccountInterface for Contract {
             .secp256r1_verify(&pk, &payload, &signature.signature);
 
         // Parse the client data JSON, extracting the base64 url encoded
-        // challenge.
-        let client_data_json = signature.client_data_json.to_buffer::<1024>();
-        let client_data_json = client_data_json.as_slice();
-        let (client_data, _): (ClientDataJson, _) =
-            serde_json_core::de::from_slice(client_data_json).map_err(|_| Error::JsonParseError)?;
+        // challenge and the origin.
+        let client_data =
+            client_data::parse(&signature.client_data_json).ok_or(Error::JsonParseError)?;
+        let challenge = client_data.challenge.ok_or(Error::JsonParseError)?;
 
         // Build what the base64 url challenge is expected.
         let mut expected_challenge = *b"___________________________________________";
@@ -105,10 +235,26 @@ impl CustomAccountInterface for Contract {
 
         // Check that the challenge inside the client data JSON that was signed
         // is identical to the expected challenge.
-        if client_data.challenge.as_bytes() != expected_challenge {
+        if challenge != Bytes::from_array(&env, &expected_challenge) {
             return Err(Error::ClientDataJsonChallengeIncorrect);
         }
 
+        // Only an assertion signs for the wallet, never a registration.
+        if client_data.type_ != Some(Bytes::from_slice(&env, b"webauthn.get")) {
+            return Err(Error::InvalidType);
+        }
+
+        // Once origins are set, the signature must come from one of them.
+        let origins = get_origins(&env);
+
+        if !origins.is_empty()
+            && !client_data
+                .origin
+                .is_some_and(|origin| origins.contains(&origin))
+        {
+            return Err(Error::OriginNotAllowed);
+        }
+
         Self::extend_ttl(env);
 
         Ok(())
@@ -0,0 +1,262 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::{Contract, ContractClient, CredentialSignature, Error, Signature};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use p256::ecdsa::{signature::Signer as _, Signature as P256Signature, SigningKey};
+use soroban_sdk::{bytes, vec, Bytes, BytesN, Env, IntoVal, InvokeError, Val};
+
+const PAYLOAD: [u8; 32] = [7; 32];
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32].into()).unwrap()
+}
+
+fn public_key(e: &Env, key: &SigningKey) -> BytesN<65> {
+    BytesN::from_array(
+        e,
+        key.verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap(),
+    )
+}
+
+/// An assertion over `PAYLOAD`, in the legacy shape without a credential id.
+fn sign(e: &Env, key: &SigningKey) -> Signature {
+    sign_as(e, key, "webauthn.get")
+}
+
+/// Like `sign`, with `type_` as the client data type.
+fn sign_as(e: &Env, key: &SigningKey, type_: &str) -> Signature {
+    let client_data_json = std::format!(
+        r#"{{"type":"{}","challenge":"{}","origin":"https://flashpool.app"}}"#,
+        type_,
+        URL_SAFE_NO_PAD.encode(PAYLOAD)
+    );
+    let authenticator_data = [5u8; 37];
+    let mut message = std::vec::Vec::from(authenticator_data);
+
+    message.extend_from_slice(
+        &e.crypto()
+            .sha256(&Bytes::from_slice(e, client_data_json.as_bytes()))
+            .to_array(),
+    );
+
+    let signature: P256Signature = key.sign(&message);
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    Signature {
+        authenticator_data: Bytes::from_slice(e, &authenticator_data),
+        client_data_json: Bytes::from_slice(e, client_data_json.as_bytes()),
+        signature: BytesN::from_array(e, &signature.to_bytes().into()),
+    }
+}
+
+fn sign_with_id(e: &Env, key: &SigningKey, id: &Bytes) -> CredentialSignature {
+    let Signature {
+        authenticator_data,
+        client_data_json,
+        signature,
+    } = sign(e, key);
+
+    CredentialSignature {
+        id: id.clone(),
+        authenticator_data,
+        client_data_json,
+        signature,
+    }
+}
+
+fn check(
+    e: &Env,
+    contract: &ContractClient,
+    signature: Val,
+) -> Result<(), Result<Error, InvokeError>> {
+    e.try_invoke_contract_check_auth::<Error>(
+        &contract.address,
+        &BytesN::from_array(e, &PAYLOAD),
+        signature,
+        &vec![e],
+    )
+}
+
+fn setup<'a>(e: &Env, legacy: &SigningKey) -> ContractClient<'a> {
+    e.mock_all_auths();
+
+    let contract = ContractClient::new(e, &e.register(Contract, ()));
+
+    contract.init(&public_key(e, legacy));
+
+    contract
+}
+
+#[test]
+fn test_legacy_signature() {
+    let e = Env::default();
+    let legacy = signing_key(1);
+    let contract = setup(&e, &legacy);
+
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+    assert_eq!(
+        contract.try_init(&public_key(&e, &signing_key(2))),
+        Err(Ok(Error::AlreadyInited))
+    );
+
+    // Before any credential is added an id matches nothing
+    assert_eq!(
+        check(
+            &e,
+            &contract,
+            sign_with_id(&e, &legacy, &bytes!(&e, 0x01)).into_val(&e)
+        ),
+        Err(Ok(Error::CredentialNotFound))
+    );
+    assert_eq!(
+        check(&e, &contract, bytes!(&e, 0x01).into_val(&e)),
+        Err(Ok(Error::Secp256r1SignatureParse))
+    );
+}
+
+#[test]
+fn test_multiple_credentials() {
+    let e = Env::default();
+    let legacy = signing_key(1);
+    let contract = setup(&e, &legacy);
+    let (phone, laptop) = (signing_key(2), signing_key(3));
+    let (phone_id, laptop_id) = (bytes!(&e, 0x02), bytes!(&e, 0x03));
+
+    contract.add_credential(&phone_id, &public_key(&e, &phone));
+
+    assert_eq!(e.auths()[0].0, contract.address);
+
+    contract.add_credential(&laptop_id, &public_key(&e, &laptop));
+
+    assert_eq!(
+        contract.try_add_credential(&phone_id, &public_key(&e, &laptop)),
+        Err(Ok(Error::CredentialExists))
+    );
+    assert_eq!(contract.credentials().len(), 2);
+
+    // Each passkey signs under its own id, and the legacy key still works
+    assert_eq!(
+        check(
+            &e,
+            &contract,
+            sign_with_id(&e, &phone, &phone_id).into_val(&e)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check(
+            &e,
+            &contract,
+            sign_with_id(&e, &laptop, &laptop_id).into_val(&e)
+        ),
+        Ok(())
+    );
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+
+    // A signature is only good for the key its id names
+    assert!(check(
+        &e,
+        &contract,
+        sign_with_id(&e, &laptop, &phone_id).into_val(&e)
+    )
+    .is_err());
+    assert!(check(&e, &contract, sign(&e, &phone).into_val(&e)).is_err());
+}
+
+#[test]
+fn test_last_credential() {
+    let e = Env::default();
+    let legacy = signing_key(1);
+    let contract = setup(&e, &legacy);
+    let phone = signing_key(2);
+    let phone_id = bytes!(&e, 0x02);
+
+    assert_eq!(
+        contract.try_remove_credential(&None),
+        Err(Ok(Error::LastCredential))
+    );
+    assert_eq!(
+        contract.try_remove_credential(&Some(phone_id.clone())),
+        Err(Ok(Error::CredentialNotFound))
+    );
+
+    // Rotating off the legacy key onto a passkey with an id
+    contract.add_credential(&phone_id, &public_key(&e, &phone));
+    contract.remove_credential(&None);
+
+    assert_eq!(
+        check(&e, &contract, sign(&e, &legacy).into_val(&e)),
+        Err(Ok(Error::NotInited))
+    );
+    assert_eq!(
+        check(
+            &e,
+            &contract,
+            sign_with_id(&e, &phone, &phone_id).into_val(&e)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        contract.try_remove_credential(&None),
+        Err(Ok(Error::CredentialNotFound))
+    );
+    assert_eq!(
+        contract.try_remove_credential(&Some(phone_id)),
+        Err(Ok(Error::LastCredential))
+    );
+}
+
+#[test]
+fn test_origin_allowlist() {
+    let e = Env::default();
+    let legacy = signing_key(1);
+    let contract = setup(&e, &legacy);
+    let origin = |origin: &str| Bytes::from_slice(&e, origin.as_bytes());
+
+    // Without an allowlist any origin goes
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+
+    contract.set_origins(&vec![&e, origin("https://flashpool.app")]);
+
+    assert_eq!(e.auths()[0].0, contract.address);
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+
+    contract.set_origins(&vec![
+        &e,
+        origin("android:apk-key-hash:kT2KqNnDXrE9aR-uq5qVvZ3M4hJ0rIqVYm2Zb8C8uVg"),
+    ]);
+
+    assert_eq!(
+        check(&e, &contract, sign(&e, &legacy).into_val(&e)),
+        Err(Ok(Error::OriginNotAllowed))
+    );
+
+    // Clearing the list lifts the restriction
+    contract.set_origins(&vec![&e]);
+
+    assert_eq!(contract.origins(), vec![&e]);
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+}
+
+#[test]
+fn test_registration_is_not_an_assertion() {
+    let e = Env::default();
+    let legacy = signing_key(1);
+    let contract = setup(&e, &legacy);
+
+    // Signed by the right key over the right challenge, but as a registration
+    assert_eq!(
+        check(
+            &e,
+            &contract,
+            sign_as(&e, &legacy, "webauthn.create").into_val(&e)
+        ),
+        Err(Ok(Error::InvalidType))
+    );
+    assert_eq!(check(&e, &contract, sign(&e, &legacy).into_val(&e)), Ok(()));
+}
@@ -16,3 +16,13 @@ pub fn write_administrator(e: &Env, id: &Address) {
     let key = DataKey::Admin;
     e.storage().instance().set(&key, id);
 }
+
+pub fn read_minter(e: &Env) -> Option<Address> {
+    let key = DataKey::Minter;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_minter(e: &Env, id: &Address) {
+    let key = DataKey::Minter;
+    e.storage().instance().set(&key, id);
+}
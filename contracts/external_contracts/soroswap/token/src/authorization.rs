@@ -0,0 +1,33 @@
+use crate::storage_types::{AuthFlags, DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
+use soroban_sdk::{Address, Env};
+
+pub fn read_auth_flags(e: &Env) -> AuthFlags {
+    let key = DataKey::AuthFlags;
+    e.storage().instance().get(&key).unwrap_or_default()
+}
+
+pub fn write_auth_flags(e: &Env, flags: &AuthFlags) {
+    let key = DataKey::AuthFlags;
+    e.storage().instance().set(&key, flags);
+}
+
+/// Balances without an explicit state are authorized unless authorization is required.
+pub fn is_authorized(e: &Env, addr: Address) -> bool {
+    let key = DataKey::State(addr);
+    if let Some(authorized) = e.storage().persistent().get::<DataKey, bool>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+        authorized
+    } else {
+        !read_auth_flags(e).required
+    }
+}
+
+pub fn write_authorization(e: &Env, addr: Address, authorize: bool) {
+    let key = DataKey::State(addr);
+    e.storage().persistent().set(&key, &authorize);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+}
@@ -1,3 +1,4 @@
+use crate::authorization::is_authorized;
 use crate::storage_types::{DataKey, BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD};
 use soroban_sdk::{Address, Env};
 
@@ -22,11 +23,22 @@ fn write_balance(e: &Env, addr: Address, amount: i128) {
 }
 
 pub fn receive_balance(e: &Env, addr: Address, amount: i128) {
+    if !is_authorized(e, addr.clone()) {
+        panic!("balance is deauthorized");
+    }
     let balance = read_balance(e, addr.clone());
     write_balance(e, addr, balance + amount);
 }
 
 pub fn spend_balance(e: &Env, addr: Address, amount: i128) {
+    if !is_authorized(e, addr.clone()) {
+        panic!("balance is deauthorized");
+    }
+    spend_balance_no_authorization_check(e, addr, amount);
+}
+
+/// Clawback takes funds from frozen balances too.
+pub fn spend_balance_no_authorization_check(e: &Env, addr: Address, amount: i128) {
     let balance = read_balance(e, addr.clone());
     if balance < amount {
         panic!("insufficient balance");
@@ -1,14 +1,22 @@
 //! This contract demonstrates a sample implementation of the Soroban token
 //! interface.
-use crate::admin::{has_administrator, read_administrator, write_administrator};
+use crate::admin::{
+    has_administrator, read_administrator, read_minter, write_administrator, write_minter,
+};
 use crate::allowance::{read_allowance, spend_allowance, write_allowance};
-use crate::balance::{read_balance, receive_balance, spend_balance};
+use crate::authorization::{is_authorized, read_auth_flags, write_auth_flags, write_authorization};
+use crate::balance::{
+    read_balance, receive_balance, spend_balance, spend_balance_no_authorization_check,
+};
 use crate::metadata::{read_decimal, read_name, read_symbol, write_metadata};
 #[cfg(test)]
 use crate::storage_types::{AllowanceDataKey, AllowanceValue, DataKey};
-use crate::storage_types::{INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
+use crate::storage_types::{AuthFlags, INSTANCE_BUMP_AMOUNT, INSTANCE_LIFETIME_THRESHOLD};
+use crate::supply::{
+    decrease_supply, increase_supply, read_max_supply, read_total_supply, write_max_supply,
+};
 use soroban_sdk::token::{self, Interface as _};
-use soroban_sdk::{contract, contractimpl, Address, Env, String};
+use soroban_sdk::{contract, contractimpl, Address, Env, String, Symbol};
 use soroban_token_sdk::metadata::TokenMetadata;
 use soroban_token_sdk::TokenUtils;
 
@@ -42,17 +50,19 @@ impl Token {
         )
     }
 
+    /// Mints with the minter's authorization, or the admin's while no minter is set.
     pub fn mint(e: Env, to: Address, amount: i128) {
         check_nonnegative_amount(amount);
-        let admin = read_administrator(&e);
-        admin.require_auth();
+        let minter = read_minter(&e).unwrap_or_else(|| read_administrator(&e));
+        minter.require_auth();
 
         e.storage()
             .instance()
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
+        increase_supply(&e, amount);
         receive_balance(&e, to.clone(), amount);
-        TokenUtils::new(&e).events().mint(admin, to, amount);
+        TokenUtils::new(&e).events().mint(minter, to, amount);
     }
 
     pub fn set_admin(e: Env, new_admin: Address) {
@@ -66,6 +76,115 @@ impl Token {
         write_administrator(&e, &new_admin);
         TokenUtils::new(&e).events().set_admin(admin, new_admin);
     }
+
+    pub fn set_minter(e: Env, new_minter: Address) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_minter(&e, &new_minter);
+        e.events()
+            .publish((Symbol::new(&e, "set_minter"), admin), new_minter);
+    }
+
+    /// Caps the total supply. A cap can be lowered to the current supply but never raised.
+    pub fn set_max_supply(e: Env, max_supply: i128) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        if max_supply < read_total_supply(&e) {
+            panic!("max supply is below total supply")
+        }
+        if read_max_supply(&e).is_some_and(|current| max_supply > current) {
+            panic!("max supply can only be lowered")
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_max_supply(&e, max_supply);
+        e.events()
+            .publish((Symbol::new(&e, "set_max_supply"), admin), max_supply);
+    }
+
+    /// As with Stellar assets, clawback can only be enabled on revocable tokens.
+    pub fn set_auth_flags(e: Env, flags: AuthFlags) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        if flags.clawback && !flags.revocable {
+            panic!("clawback requires revocable authorization")
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_auth_flags(&e, &flags);
+        e.events()
+            .publish((Symbol::new(&e, "set_auth_flags"), admin), flags);
+    }
+
+    /// Authorizes or freezes a balance. Frozen balances can't send, receive or burn.
+    pub fn set_authorized(e: Env, id: Address, authorize: bool) {
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        if !authorize && !read_auth_flags(&e).revocable {
+            panic!("authorization is not revocable")
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        write_authorization(&e, id.clone(), authorize);
+        TokenUtils::new(&e)
+            .events()
+            .set_authorized(admin, id, authorize);
+    }
+
+    pub fn clawback(e: Env, from: Address, amount: i128) {
+        check_nonnegative_amount(amount);
+        let admin = read_administrator(&e);
+        admin.require_auth();
+
+        if !read_auth_flags(&e).clawback {
+            panic!("clawback is not enabled")
+        }
+
+        e.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+
+        spend_balance_no_authorization_check(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
+        TokenUtils::new(&e).events().clawback(admin, from, amount);
+    }
+
+    pub fn total_supply(e: Env) -> i128 {
+        read_total_supply(&e)
+    }
+
+    pub fn max_supply(e: Env) -> Option<i128> {
+        read_max_supply(&e)
+    }
+
+    pub fn minter(e: Env) -> Option<Address> {
+        read_minter(&e)
+    }
+
+    pub fn auth_flags(e: Env) -> AuthFlags {
+        read_auth_flags(&e)
+    }
+
+    pub fn authorized(e: Env, id: Address) -> bool {
+        is_authorized(&e, id)
+    }
 }
 
 
@@ -139,6 +258,7 @@ impl token::Interface for Token {
             .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
 
         spend_balance(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
         TokenUtils::new(&e).events().burn(from, amount);
     }
 
@@ -153,6 +273,7 @@ impl token::Interface for Token {
 
         spend_allowance(&e, from.clone(), spender, amount);
         spend_balance(&e, from.clone(), amount);
+        decrease_supply(&e, amount);
         TokenUtils::new(&e).events().burn(from, amount)
     }
 
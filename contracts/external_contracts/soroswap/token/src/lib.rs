@@ -2,10 +2,13 @@
 
 mod admin;
 mod allowance;
+mod authorization;
 mod balance;
 mod contract;
 mod metadata;
 mod storage_types;
+mod supply;
 mod test;
 
 pub use crate::contract::TokenClient;
+pub use crate::storage_types::AuthFlags;
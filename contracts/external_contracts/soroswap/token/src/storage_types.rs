@@ -20,6 +20,17 @@ pub struct AllowanceValue {
     pub expiration_ledger: u32,
 }
 
+/// Stellar asset style authorization flags. `required` leaves new balances
+/// unauthorized until the admin authorizes them, `revocable` lets the admin
+/// freeze a balance again, and `clawback` lets the admin take funds back.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct AuthFlags {
+    pub required: bool,
+    pub revocable: bool,
+    pub clawback: bool,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -28,4 +39,8 @@ pub enum DataKey {
     Nonce(Address),
     State(Address),
     Admin,
+    Minter,
+    TotalSupply,
+    MaxSupply,
+    AuthFlags,
 }
@@ -0,0 +1,38 @@
+use crate::storage_types::DataKey;
+use soroban_sdk::Env;
+
+pub fn read_total_supply(e: &Env) -> i128 {
+    let key = DataKey::TotalSupply;
+    e.storage().instance().get(&key).unwrap_or(0)
+}
+
+fn write_total_supply(e: &Env, amount: i128) {
+    let key = DataKey::TotalSupply;
+    e.storage().instance().set(&key, &amount);
+}
+
+pub fn read_max_supply(e: &Env) -> Option<i128> {
+    let key = DataKey::MaxSupply;
+    e.storage().instance().get(&key)
+}
+
+pub fn write_max_supply(e: &Env, amount: i128) {
+    let key = DataKey::MaxSupply;
+    e.storage().instance().set(&key, &amount);
+}
+
+pub fn increase_supply(e: &Env, amount: i128) {
+    let total_supply = read_total_supply(e)
+        .checked_add(amount)
+        .expect("total supply overflow");
+    if let Some(max_supply) = read_max_supply(e) {
+        if total_supply > max_supply {
+            panic!("max supply exceeded");
+        }
+    }
+    write_total_supply(e, total_supply);
+}
+
+pub fn decrease_supply(e: &Env, amount: i128) {
+    write_total_supply(e, read_total_supply(e) - amount);
+}
@@ -1,11 +1,11 @@
 #![cfg(test)]
 extern crate std;
 
-use crate::{contract::Token, TokenClient};
+use crate::{contract::Token, AuthFlags, TokenClient};
 use soroban_sdk::{
     symbol_short,
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
-    Address, Env, IntoVal, Symbol,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events},
+    vec, Address, Env, IntoVal, Symbol,
 };
 
 fn create_token<'a>(e: &Env, admin: &Address) -> TokenClient<'a> {
@@ -271,3 +271,221 @@ fn test_zero_allowance() {
     token.transfer_from(&spender, &from, &spender, &0);
     assert!(token.get_allowance(&from, &spender).is_none());
 }
+
+#[test]
+fn test_supply() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.mint(&user1, &1000);
+    token.approve(&user1, &user2, &100, &200);
+    token.burn_from(&user2, &user1, &100);
+    token.burn(&user1, &100);
+    assert_eq!(token.total_supply(), 800);
+    assert_eq!(token.max_supply(), None);
+
+    token.set_max_supply(&1000);
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                token.address.clone(),
+                (Symbol::new(&e, "set_max_supply"), admin.clone()).into_val(&e),
+                1000_i128.into_val(&e),
+            )
+        ]
+    );
+
+    token.mint(&user2, &200);
+    assert_eq!(token.total_supply(), 1000);
+    assert_eq!(token.max_supply(), Some(1000));
+
+    // Burning frees room under the cap
+    token.burn(&user2, &50);
+    token.mint(&user1, &50);
+    assert_eq!(token.total_supply(), 1000);
+}
+
+#[test]
+#[should_panic(expected = "max supply exceeded")]
+fn mint_over_max_supply() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_max_supply(&1000);
+    token.mint(&user, &1000);
+    token.mint(&user, &1);
+}
+
+#[test]
+#[should_panic(expected = "max supply can only be lowered")]
+fn raise_max_supply() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_max_supply(&1000);
+    token.set_max_supply(&500);
+    token.set_max_supply(&1000);
+}
+
+#[test]
+fn test_minter() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let minter = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_minter(&minter);
+    assert_eq!(token.minter(), Some(minter.clone()));
+
+    token.mint(&user, &1000);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            minter.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    token.address.clone(),
+                    symbol_short!("mint"),
+                    (&user, 1000_i128).into_val(&e),
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                token.address.clone(),
+                (symbol_short!("mint"), minter.clone(), user.clone()).into_val(&e),
+                1000_i128.into_val(&e),
+            )
+        ]
+    );
+    assert_eq!(token.balance(&user), 1000);
+}
+
+#[test]
+fn test_freeze_and_clawback() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_auth_flags(&AuthFlags {
+        required: true,
+        revocable: true,
+        clawback: true,
+    });
+    assert!(!token.authorized(&user1));
+
+    token.set_authorized(&user1, &true);
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                token.address.clone(),
+                (
+                    Symbol::new(&e, "set_authorized"),
+                    admin.clone(),
+                    user1.clone()
+                )
+                    .into_val(&e),
+                true.into_val(&e),
+            )
+        ]
+    );
+    token.set_authorized(&user2, &true);
+    token.mint(&user1, &1000);
+    token.transfer(&user1, &user2, &100);
+
+    // Frozen balances can still be clawed back
+    token.set_authorized(&user1, &false);
+    assert!(!token.authorized(&user1));
+
+    token.clawback(&user1, &400);
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                token.address.clone(),
+                (symbol_short!("clawback"), admin.clone(), user1.clone()).into_val(&e),
+                400_i128.into_val(&e),
+            )
+        ]
+    );
+    assert_eq!(token.balance(&user1), 500);
+    assert_eq!(token.total_supply(), 600);
+}
+
+#[test]
+#[should_panic(expected = "balance is deauthorized")]
+fn transfer_from_frozen_balance() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user1 = Address::generate(&e);
+    let user2 = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.mint(&user1, &1000);
+    token.set_auth_flags(&AuthFlags {
+        required: false,
+        revocable: true,
+        clawback: false,
+    });
+    token.set_authorized(&user1, &false);
+
+    token.transfer(&user1, &user2, &100);
+}
+
+#[test]
+#[should_panic(expected = "authorization is not revocable")]
+fn freeze_without_revocable() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.set_authorized(&user, &false);
+}
+
+#[test]
+#[should_panic(expected = "clawback is not enabled")]
+fn clawback_not_enabled() {
+    let e = Env::default();
+    e.mock_all_auths();
+
+    let admin = Address::generate(&e);
+    let user = Address::generate(&e);
+    let token = create_token(&e, &admin);
+
+    token.mint(&user, &1000);
+    token.clawback(&user, &100);
+}
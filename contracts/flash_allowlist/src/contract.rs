@@ -0,0 +1,26 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+use crate::sorted_pair;
+
+/// Leaf = sha256(xdr(user) || max_amount as 16 big-endian bytes).
+pub fn leaf(e: &Env, user: &Address, max_amount: i128) -> BytesN<32> {
+    let mut b = user.clone().to_xdr(e);
+    b.extend_from_array(&max_amount.to_be_bytes());
+    e.crypto().sha256(&b).into()
+}
+
+pub fn hash_pair(e: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (lo, hi) = sorted_pair(a, b);
+    let mut buf = Bytes::from_array(e, &lo.to_array());
+    buf.extend_from_array(&hi.to_array());
+    e.crypto().sha256(&buf).into()
+}
+
+/// Folds a proof onto a leaf and compares with `root`.
+pub fn verify(e: &Env, root: &BytesN<32>, leaf: BytesN<32>, proof: &Vec<BytesN<32>>) -> bool {
+    let mut node = leaf;
+    for sibling in proof.iter() {
+        node = hash_pair(e, &node, &sibling);
+    }
+    node == *root
+}
@@ -0,0 +1,26 @@
+//! flash_allowlist
+//! -------------------------------------------------------------
+//! Campaign allowlists. The off-chain builder (the default `std` feature)
+//! produces the merkle root passed to `create_campaign` and the per-user
+//! proofs passed to `join_campaign`; `contract` is the `no_std` side that
+//! `flash_campaign_manager` verifies them with.
+//!
+//! Leaf   = sha256( xdr(ScVal::Address(user)) || max_amount as 16 big-endian bytes )
+//! Node   = sha256( min(a, b) || max(a, b) )
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod contract;
+#[cfg(feature = "std")]
+mod tree;
+
+#[cfg(feature = "std")]
+pub use tree::*;
+
+/// Sibling pairs are hashed in sorted order so proofs carry no left/right bits.
+fn sorted_pair<'a, T: PartialOrd>(a: &'a T, b: &'a T) -> (&'a T, &'a T) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
@@ -0,0 +1,178 @@
+use sha2::{Digest, Sha256};
+use soroban_sdk::xdr::{Limits, ScAddress, ScVal, WriteXdr};
+
+use crate::sorted_pair;
+
+pub type Hash = [u8; 32];
+
+/// One allowlisted user and the most token0 they may deposit in total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub user: ScAddress,
+    pub max_amount: i128,
+}
+
+impl Entry {
+    /// Parses a `G...` or `C...` strkey.
+    pub fn new(user: &str, max_amount: i128) -> Result<Self, String> {
+        let user = user
+            .parse::<ScAddress>()
+            .map_err(|e| format!("bad address {user}: {e:?}"))?;
+        Ok(Entry { user, max_amount })
+    }
+}
+
+/// Bytes hashed into a leaf; matches `Address::to_xdr` on the contract side.
+pub fn leaf_preimage(user: &ScAddress, max_amount: i128) -> Vec<u8> {
+    let mut out = ScVal::Address(user.clone())
+        .to_xdr(Limits::none())
+        .expect("address xdr");
+    out.extend_from_slice(&max_amount.to_be_bytes());
+    out
+}
+
+pub fn leaf_hash(user: &ScAddress, max_amount: i128) -> Hash {
+    Sha256::digest(leaf_preimage(user, max_amount)).into()
+}
+
+pub fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+    let (lo, hi) = sorted_pair(a, b);
+    let mut h = Sha256::new();
+    h.update(lo);
+    h.update(hi);
+    h.finalize().into()
+}
+
+/// Folds a proof onto a leaf and compares with `root`.
+pub fn verify(root: &Hash, leaf: &Hash, proof: &[Hash]) -> bool {
+    let mut node = *leaf;
+    for sibling in proof {
+        node = hash_pair(&node, sibling);
+    }
+    node == *root
+}
+
+/// Full tree, kept so proofs can be produced for every entry.
+///
+/// An odd node at the end of a layer is carried up unchanged, so it simply
+/// has one sibling fewer in its proof.
+#[derive(Clone, Debug)]
+pub struct AllowlistTree {
+    entries: Vec<Entry>,
+    layers: Vec<Vec<Hash>>,
+}
+
+impl AllowlistTree {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        assert!(!entries.is_empty(), "allowlist needs at least one entry");
+        let leaves: Vec<Hash> = entries
+            .iter()
+            .map(|en| leaf_hash(&en.user, en.max_amount))
+            .collect();
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let prev = layers.last().unwrap();
+            let next = prev
+                .chunks(2)
+                .map(|c| if c.len() == 2 { hash_pair(&c[0], &c[1]) } else { c[0] })
+                .collect();
+            layers.push(next);
+        }
+        AllowlistTree { entries, layers }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.layers.last().unwrap()[0]
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn proof(&self, index: usize) -> Vec<Hash> {
+        assert!(index < self.entries.len(), "index out of range");
+        let mut proof = Vec::new();
+        let mut i = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sib = i ^ 1;
+            if sib < layer.len() {
+                proof.push(layer[sib]);
+            }
+            i /= 2;
+        }
+        proof
+    }
+
+    /// Proof for the first entry matching `user`, with its cap.
+    pub fn proof_for(&self, user: &ScAddress) -> Option<(i128, Vec<Hash>)> {
+        let i = self.entries.iter().position(|en| en.user == *user)?;
+        Some((self.entries[i].max_amount, self.proof(i)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::contract;
+    use soroban_sdk::{testutils::Address as _, Address, BytesN, Env};
+
+    fn entries(e: &Env, n: usize) -> (std::vec::Vec<Address>, std::vec::Vec<Entry>) {
+        let users: std::vec::Vec<Address> = (0..n).map(|_| Address::generate(e)).collect();
+        let list = users
+            .iter()
+            .enumerate()
+            .map(|(i, u)| Entry {
+                user: u.into(),
+                max_amount: (i as i128 + 1) * 1_000 * 10_000_000,
+            })
+            .collect();
+        (users, list)
+    }
+
+    #[test]
+    fn leaf_matches_env_encoding() {
+        let e = Env::default();
+        let user = Address::generate(&e);
+        let on_chain: Hash = contract::leaf(&e, &user, 42).to_array();
+        assert_eq!(leaf_hash(&(&user).into(), 42), on_chain);
+    }
+
+    #[test]
+    fn proofs_verify_on_chain() {
+        let e = Env::default();
+        let (users, list) = entries(&e, 5);
+        let tree = AllowlistTree::new(list.clone());
+        let root = BytesN::from_array(&e, &tree.root());
+        for (i, en) in list.iter().enumerate() {
+            let mut proof = soroban_sdk::Vec::new(&e);
+            for sibling in tree.proof(i) {
+                proof.push_back(BytesN::from_array(&e, &sibling));
+            }
+            let leaf = contract::leaf(&e, &users[i], en.max_amount);
+            assert!(contract::verify(&e, &root, leaf, &proof), "i={i}");
+        }
+    }
+
+    #[test]
+    fn every_proof_verifies() {
+        let e = Env::default();
+        for n in [1usize, 2, 3, 5, 8, 13] {
+            let (_, list) = entries(&e, n);
+            let tree = AllowlistTree::new(list.clone());
+            for (i, en) in list.iter().enumerate() {
+                let leaf = leaf_hash(&en.user, en.max_amount);
+                assert!(verify(&tree.root(), &leaf, &tree.proof(i)), "n={n} i={i}");
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_amount_fails() {
+        let e = Env::default();
+        let (users, list) = entries(&e, 4);
+        let tree = AllowlistTree::new(list);
+        let user: ScAddress = (&users[1]).into();
+        let (max, proof) = tree.proof_for(&user).unwrap();
+        assert!(!verify(&tree.root(), &leaf_hash(&user, max + 1), &proof));
+    }
+}
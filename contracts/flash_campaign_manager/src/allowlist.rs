@@ -0,0 +1,13 @@
+use soroban_sdk::{contracttype, BytesN, Vec};
+
+// Same encoding the off-chain builder uses, so roots can't drift apart
+pub use flash_allowlist::contract::{leaf, verify};
+
+/// Proof that `(user, max_amount)` is a leaf of a campaign's allowlist root.
+/// Built off-chain with the `flash_allowlist` crate.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowProof {
+    pub max_amount: i128,
+    pub proof: Vec<BytesN<32>>,
+}
@@ -5,15 +5,14 @@
 #![no_std]
 
 use soroban_sdk::bytesn;
-use soroban_sdk::xdr::FromXdr;
 // -------------------------------------------------------------
 // Imports
 //
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, token::Client as TokenClient, unwrap::UnwrapOptimized, Address, BytesN, Env, IntoVal, Val, xdr::ToXdr, Bytes
+    contract, contracterror, contractimpl, contracttype, token::Client as TokenClient, Address, BytesN, Env, IntoVal, Val, Vec, xdr::ToXdr, Bytes
 };
 use soroban_sdk::auth::Context;
-use smart_wallet_interface::types::Signatures;
+use smart_wallet_interface::types::{Signatures, Signer};
 // bring the real Soroswap pair WASM (for on-chain build)
 pub mod pair {
     soroban_sdk::contractimport!(file = "../target/wasm32v1-none/release/soroswap_pair.wasm");
@@ -23,14 +22,18 @@ mod soroswap_factory {
     soroban_sdk::contractimport!(file = "../target/wasm32v1-none/release/soroswap_factory.wasm");
 }
 
+mod allowlist;
+mod position;
 mod rewards;
 mod storage;
 mod utils;
 mod account_contract {
     use soroban_sdk::auth::Context;
-    use smart_wallet_interface::types::Signatures;
+    use smart_wallet_interface::types::{Signatures, Signer, SignerKey};
+    use smart_wallet_interface::upgrade::PendingRemoval;
     soroban_sdk::contractimport!(file = "../target/wasm32v1-none/release/account.wasm");
 }
+use allowlist::AllowProof;
 use storage::*;
 use utils::*;
 
@@ -64,8 +67,25 @@ pub enum FlashErr {
     Maf = 18,
     Mar = 19,
     InvalidToken = 20,
-    CampaignActiveForPair = 21,
     NoCorePair = 22,
+    NotAllowlisted = 23,
+    AllowlistCapExceeded = 24,
+    InvalidLimits = 25,
+    BelowMinDeposit = 26,
+    UserCapExceeded = 27,
+    HardCapReached = 28,
+    CampaignEnded = 29,
+    DurationTooLong = 30,
+    NoSuchProposal = 31,
+    PledgeClosed = 32,
+    PledgeLocked = 33,
+    NotCreator = 34,
+    DistributionOff = 35,
+    NotPositionOwner = 36,
+    AccountSignerRequired = 38,
+    InvalidPledgeWindow = 39,
+    AccountRpIdNotSet = 40,
+    NotApproved = 41,
 }
 
 /// assert-style helper that logs **before** panicking
@@ -89,6 +109,9 @@ const MAX_BPS: u32 = 10_000;
 const DEFAULT_SURPLUS_BPS: u32 = 500; // 5 %
 const DEFAULT_TTL_THRESH: u32 = 172_800; // 10 days
 const DEFAULT_TTL_BUMP: u32 = 241_920; // 14 days bump
+const MAX_DURATION: u32 = 3_110_400; // ~180 days, cap on a campaign's total length once extended
+const MAX_KEEPER_BPS: u32 = 100; // 1 %, cap on the distribute bounty
+const MAX_PLEDGE_WINDOW: u32 = 518_400; // ~30 days, cap on how long pledges stay open
 
 // -------------------------------------------------------------
 // Data types
@@ -105,6 +128,65 @@ pub struct Campaign {
     reward_flash: i128,
     bonus_flash: i128,
     stake_lp: i128,
+    allow_root: Option<BytesN<32>>, // merkle root of (user, max_amount) leaves, None = open
+    limits: CampaignLimits,
+    creator: Address,
+    keeper_bps: Option<u32>, // Some = anyone may `distribute`, for this cut of each payout
+}
+
+/// Join limits set by the creator. `min_deposit` and `max_deposit_per_user`
+/// are in the pair's token0, `hard_cap_lp` is in campaign LP.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CampaignLimits {
+    pub min_deposit: i128,                  // 0 = no minimum
+    pub max_deposit_per_user: Option<i128>, // summed over repeat joins
+    pub hard_cap_lp: Option<i128>,          // joins refused once total_lp would pass it
+}
+
+/// Everything `create_campaign` needs apart from the fee and the creator.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignParams {
+    pub pair: Address,
+    pub unlock: u32,
+    pub target_lp: i128,
+    pub bonus_flash: i128,
+    pub allow_root: Option<BytesN<32>>,
+    pub limits: CampaignLimits,
+}
+
+/// A co-sponsored campaign waiting for its pledges. USDC stays escrowed here
+/// until `pledged` reaches `threshold_usdc`, which must happen by `deadline`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub params: CampaignParams,
+    pub proposer: Address,
+    pub threshold_usdc: i128,
+    pub deadline: u32,             // last ledger a pledge is accepted
+    pub pledged: i128,
+    pub sponsors: Vec<Address>,    // in pledge order
+    pub campaign_id: Option<u32>,  // set once activated
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Claimed,
+    NotEnded,
+    NothingToClaim,
+    Failed(u32), // FlashErr code the claim failed with
+}
+
+/// One entry of `claim_many` / `claim_all`; amounts are zero unless claimed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimResult {
+    pub id: u32,
+    pub status: ClaimStatus,
+    pub flash: i128,
+    pub lp: i128,
 }
 
 #[contracttype]
@@ -114,6 +196,8 @@ pub struct UserPos {
     weight: i128,
     joined_ledger: u32, // track when user joined
     rank: u32,          // track user rank in campaign
+    deposit: i128,      // token0 deposited over all joins
+    lp_held: i128,      // LP escrowed for this position and returned at claim
 }
 
 // -------------------------------------------------------------
@@ -186,6 +270,596 @@ fn swap_usdc_to_flash(
     out
 }
 
+// -------------------------------------------------------------
+// Fee pipeline (USDC -> FLASH reward pool)
+// -------------------------------------------------------------
+/// Pulls `fee_usdc` from `payer`, swaps the surplus share to FLASH, adds the
+/// rest as FLASH/USDC liquidity and returns the FLASH left for rewards.
+/// `payer = None` means the USDC is already escrowed here (pledges).
+/// Shared by `create_campaign`, `top_up_campaign` and pledge activation.
+fn fee_to_reward(
+    e: &Env,
+    config: &CoreConfig,
+    fee_usdc: i128,
+    payer: Option<&Address>,
+) -> Result<i128, FlashErr> {
+    let cca = e.current_contract_address();
+    let core_pair_address = config
+        .core_pair
+        .clone()
+       .ok_or(FlashErr::NoCorePair)?;
+    let core_pair = pair::Client::new(e, &core_pair_address);
+
+    let flash_address = config.flash.clone();
+    let usdc_address = config.usdc.clone();
+    let surplus_bps = config.surplus_bps;
+    ensure!(e, surplus_bps < MAX_BPS, FlashErr::BpsOutOfRange);
+    let usd_cli = TokenClient::new(e, &usdc_address);
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!(
+        "    {payer:?} paying {:.7} USDC",
+        (fee_usdc / 10i128.pow(7)) as f64
+    );
+
+    // Take all USDC from payer
+    if let Some(payer) = payer {
+        usd_cli.transfer(payer, &cca, &fee_usdc);
+    }
+
+    let (r0, r1) = core_pair.get_reserves();
+    let (t0, t1) = o_t_c(e, &flash_address, &usdc_address);
+    let t0_addr = t0.address.clone();
+    let t1_addr = t1.address.clone();
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!(
+        "    Raw pair reserves: {:?}={:.7}, {:?}={:.7}",
+        t0_addr, (r0 as f64) * 1e-7,
+        t1_addr, (r1 as f64) * 1e-7
+    );
+    // Map reserves so reserve_usdc_before / reserve_flash_before are correct
+    let (reserve_usdc_before, reserve_flash_before) =
+        if t0_addr == usdc_address && t1_addr == flash_address {
+            (r0, r1)
+        } else if t0_addr == flash_address && t1_addr == usdc_address {
+            (r1, r0)
+        } else {
+            return Err(FlashErr::InvalidToken);
+        };
+#[cfg(all(not(target_family = "wasm")))]
+    std::println!(
+        "    Pair reserves before (USDC→FLASH):\n        USDC={:.7}\n        FLASH={:.7}",
+        (reserve_usdc_before as f64) * 1e-7,
+        (reserve_flash_before as f64) * 1e-7
+    );
+
+    
+    // Fee split
+    let s_min = int_sqrt(
+        (reserve_usdc_before as u128) * (reserve_usdc_before as u128 + fee_usdc as u128),
+    ) as i128
+        - reserve_usdc_before;
+
+    #[cfg(all(not(target_family = "wasm")))]
+    {
+        let smin_log = s_min as f64 * 1e-7;
+        let usdc_fee_log = fee_usdc as f64 * 1e-7;
+        std::println!(
+            "    s_min={smin_log}, surplus_bps={surplus_bps}, fee_usdc={usdc_fee_log}"
+        );
+    }
+    let swap_amount = (s_min
+        + (fee_usdc as i128 * surplus_bps as i128) as i128 / MAX_BPS as i128)
+        .min(fee_usdc);
+    let usdc_liq = fee_usdc - swap_amount;
+    #[cfg(not(target_family = "wasm"))] {
+        let total_amt = swap_amount + usdc_liq;
+        std::println!(
+            "    Fee Split Calculation:\n        fee_usdc={:.7}\n        TotalAmount={:.7}\n        s_min={:.7}\n        surplus_bps={}\n        swap_amount={:.7} USDC\n        usdc_liq={:.7} USDC",
+            (fee_usdc as f64)   * 1e-7,
+            (total_amt as f64)  * 1e-7,
+            (s_min as f64)      * 1e-7,
+            surplus_bps,
+            (swap_amount as f64)* 1e-7,
+            (usdc_liq as f64)   * 1e-7
+        );
+    }
+            
+    let is_t0_usdc = t0_addr == usdc_address;
+    // swap: send USDC_in then instruct pair.swap
+    usd_cli.transfer(&cca, &core_pair_address, &swap_amount);
+    
+     let flash_out = c_s_o1(swap_amount, reserve_usdc_before, reserve_flash_before);
+
+    //let flash_out = c_s_o(swap_amount, usdc_reserves_before, flash_reserves_before);
+
+    ensure!(e, flash_out > 0 && flash_out < reserve_flash_before, FlashErr::Math);
+
+    //todo: make this more efficient, see utils::line5
+    let (out_0, out_1) = if is_t0_usdc {
+        (0, flash_out) // token0 = USDC, token1 = FLASH
+    } else {
+        (flash_out, 0) // token0 = FLASH, token1 = USDC
+    };
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!("    Swapping\n        swap_amount={:.7} USDC\n        flash_out={:.7}\n        out0={out_0}\n        out1={out_1}", (swap_amount as f64 * 1e-7) as f64, (flash_out as f64 * 1e-7) as f64);
+
+    core_pair.swap(&out_0, &out_1, &e.current_contract_address());
+
+    // After swap
+    let usdc_reserves_post_swap = reserve_usdc_before + swap_amount;
+    let flash_reserves_post_swap = reserve_flash_before - flash_out;
+    #[cfg(all(not(target_family = "wasm")))]
+    {
+    let (pr1, pr0) = core_pair.get_reserves();
+    std::println!(
+        "Post-swap reserves: \n   FLASH={:.7}\n    USDC={:.7}\n   pr0={:.7}\n   pr1={:.7}", flash_reserves_post_swap as f64 * 1e-7, usdc_reserves_post_swap as f64 * 1e-7, pr0 as f64 * 1e-7, pr1 as f64 * 1e-7
+    );
+
+    }
+   
+    // Flash needed for add_liq
+    ensure!(e, usdc_reserves_post_swap > 0, FlashErr::Math);
+
+    let flash_needed = usdc_liq
+        .checked_mul(flash_reserves_post_swap)
+        .unwrap()
+        .checked_div(usdc_reserves_post_swap)
+        .unwrap();
+
+    #[cfg(all(not(target_family = "wasm")))]
+    {
+        let flash_needed_pretty = flash_needed as f64 * 1e-7;
+
+        let usdc_liq_amount_pretty = usdc_liq as f64 * 1e-7;
+        std::println!("Calculated flash_needed={flash_needed_pretty} for usdc_liq={usdc_liq_amount_pretty} USDC (flash_reserves_post_swap={flash_reserves_post_swap}, usdc_reserves_post_swap={usdc_reserves_post_swap})");
+
+        let (a0, a1) = if is_t0_usdc {
+            (flash_needed, usdc_liq)
+        } else {
+            (usdc_liq, flash_needed)
+        };
+
+        std::println!(
+            "Transferred: token_0={:?} amount_0={:.7}, token_1={:?} amount_1={:.7}",
+            t0_addr,
+            (a0 as f64 * 1e-7) as f64,
+            t1_addr,
+            (a1 as f64 * 1e-7) as f64
+        );
+
+        // Log contract's pool-related token balances before deposit
+        let t0_balance = t0.balance(&e.current_contract_address());
+        let t1_balance = t1.balance(&e.current_contract_address());
+        std::println!(
+            "Contract balances before xfer: \n    token_0={:?}={:.7}\n    token_1={:?}={:.7}",
+            t0_addr,
+            (t0_balance as f64 * 1e-7) as f64,
+            t1_addr,
+            (t1_balance as f64 * 1e-7) as f64
+        );
+    }
+
+    let lpm = d_t_p(
+        &e,
+        &core_pair_address,
+        &flash_address,
+        &usdc_address,
+        flash_needed,
+        usdc_liq,
+    );
+    ensure!(e, lpm > 0, FlashErr::Math);
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!("Deposit complete. flash/usdc lp minted={:.7}", lpm as f64 * 1e-7);
+
+    // 7. reward pool calc
+    let actual_usdc = TokenClient::new(e, &usdc_address).balance(&core_pair_address)
+        - usdc_reserves_post_swap;
+    let actual_flash = TokenClient::new(e, &flash_address).balance(&core_pair_address)
+        - flash_reserves_post_swap;
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!(
+        "Actual deposit amounts:\n    actual_flsh={:.7}\n    actual_usdc={:.7}",
+        (actual_flash as f64 * 1e-7) as f64,
+        (actual_usdc as f64 * 1e-7) as f64
+    );
+    // compare and verify reserves and balances and math is correct below.
+    let ru1 = usdc_reserves_post_swap + actual_usdc;
+    let rf1 = flash_reserves_post_swap + actual_flash;
+    let root = int_sqrt(
+        (ru1 as u128 * rf1 as u128 * reserve_flash_before as u128)
+            / (reserve_usdc_before as u128),
+    );
+    let x_max = if root > rf1 as u128 {
+        (root - rf1 as u128) as i128
+    } else {
+        0
+    };
+
+    let surplus = flash_out + actual_flash - flash_needed;
+    let reward_flash = surplus.min(x_max);
+    #[cfg(all(not(target_family = "wasm")))]{
+    std::println!("Reward pool calculation: ru1={ru1}, rf1={rf1}, root={root}, x_max={x_max}, surplus={surplus}, reward_flash={reward_flash}");
+    std::println!(
+        "Reward pool calculation:\n    ru1={:.7}\n    rf1={:.7}\n    root={:.7}\n    x_max={:.7}\n    surplus={:.7}\n    reward_flash={:.7}",
+        (ru1 as f64 * 1e-7) as f64,
+        (rf1 as f64 * 1e-7) as f64,
+        (root as f64 * 1e-7) as f64,
+        (x_max as f64 * 1e-7) as f64,
+        (surplus as f64 * 1e-7) as f64,
+        (reward_flash as f64 * 1e-7) as f64
+    );
+    }
+    Ok(reward_flash)
+}
+
+/// Stores a new campaign holding `reward_flash` and indexes it under its pair.
+fn open_campaign(e: &Env, p: &CampaignParams, reward_flash: i128, creator: &Address) -> u32 {
+    let mut config = get_core_config(e);
+    let id = config.next + 1;
+    config.next = id;
+    set_core_config(e, &config);
+
+    let end_ledger = e.ledger().sequence() + p.unlock;
+    save_camp(
+        e,
+        id,
+        &Campaign {
+            pair: p.pair.clone(),
+            duration: p.unlock,
+            end_ledger,
+            target_lp: p.target_lp,
+            total_lp: 0,
+            total_weight: 0,
+            reward_flash,
+            bonus_flash: p.bonus_flash,
+            stake_lp: 0,
+            allow_root: p.allow_root.clone(),
+            limits: p.limits.clone(),
+            creator: creator.clone(),
+            keeper_bps: None,
+        },
+    );
+    add_pair_campaign(e, &p.pair, &ActiveCampaignInfo { campaign_id: id, end_ledger });
+    id
+}
+
+// -------------------------------------------------------------
+// Join helpers
+// -------------------------------------------------------------
+/// Checks a join against the campaign's allowlist, if it has one, and
+/// records the amount against the leaf's cap.
+fn check_allowlist(
+    e: &Env,
+    id: u32,
+    user: &Address,
+    token0_amt: i128,
+    proof: Option<AllowProof>,
+) -> Result<(), FlashErr> {
+    let c = load_camp(e, id);
+    let root = match c.allow_root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+    let p = proof.ok_or(FlashErr::NotAllowlisted)?;
+    let leaf = allowlist::leaf(e, user, p.max_amount);
+    ensure!(e, allowlist::verify(e, &root, leaf, &p.proof), FlashErr::NotAllowlisted);
+
+    let used = get_allow_used(e, id, user) + token0_amt;
+    ensure!(e, used <= p.max_amount, FlashErr::AllowlistCapExceeded);
+    set_allow_used(e, id, user, used);
+    Ok(())
+}
+
+/// Creation-time sanity checks for a campaign's join limits.
+fn check_limits(_e: &Env, l: &CampaignLimits, target_lp: i128) -> Result<(), FlashErr> {
+    ensure!(_e, l.min_deposit >= 0, FlashErr::InvalidLimits);
+    if let Some(max) = l.max_deposit_per_user {
+        ensure!(_e, max > 0 && max >= l.min_deposit, FlashErr::InvalidLimits);
+    }
+    if let Some(cap) = l.hard_cap_lp {
+        // a cap under the target would make the bonus unreachable
+        ensure!(_e, cap > 0 && cap >= target_lp, FlashErr::InvalidLimits);
+    }
+    Ok(())
+}
+
+/// Pre-deposit checks of a campaign's join limits for `user`.
+fn check_join(
+    e: &Env,
+    id: u32,
+    c: &Campaign,
+    user: &Address,
+    token0_amt: i128,
+) -> Result<(), FlashErr> {
+    ensure!(e, token0_amt >= c.limits.min_deposit, FlashErr::BelowMinDeposit);
+    if let Some(max) = c.limits.max_deposit_per_user {
+        let before = e
+            .storage()
+            .instance()
+            .get::<Val, UserPos>(&upos_key(e, id, user))
+            .map(|p| p.deposit)
+            .unwrap_or(0);
+        ensure!(e, before + token0_amt <= max, FlashErr::UserCapExceeded);
+    }
+    if let Some(cap) = c.limits.hard_cap_lp {
+        ensure!(e, c.total_lp < cap, FlashErr::HardCapReached);
+    }
+    Ok(())
+}
+
+/// Post-deposit check: would `lp` more push the campaign past its hard cap?
+fn fits_hard_cap(c: &Campaign, lp: i128) -> bool {
+    c.limits.hard_cap_lp.map_or(true, |cap| c.total_lp + lp <= cap)
+}
+
+/// Zaps `token0_amt` of token0 from `user` into `pair` (half swapped to
+/// token1) and returns the LP minted to this contract.
+fn zap_in(e: &Env, pair: &Address, token0_amt: i128, user: &Address) -> Result<i128, FlashErr> {
+    let cca = e.current_contract_address();
+    let pcli = pair::Client::new(e, pair);
+    let t0 = pcli.token_0();
+    let t1 = pcli.token_1();
+    let t0cli = TokenClient::new(e, &t0);
+    let t1cli = TokenClient::new(e, &t1);
+    t0cli.transfer(user, &cca, &token0_amt);
+    //log!(&e, "[JOIN CAMPAIGN] deposit {} of token0 from {:?}", token0_amt, user);
+
+    let half = token0_amt / 2;
+    t0cli.transfer(&cca, pair, &half);
+    let (r0, r1) = pcli.get_reserves();
+    // pair charges 0.3 % on the input side and checks K net of it, so the
+    // fee-less quote r1 * half / (r0 + half) is always refused with
+    // SwapKConstantNotMet; quote with the fee instead
+    let t1_out = c_s_o(half, r0, r1);
+    let (o0, o1) = if t0 < t1 { (0, t1_out) } else { (t1_out, 0) };
+    pcli.swap(&o0, &o1, &cca);
+    //log!(&e, "[JOIN CAMPAIGN] swap_half: {} token0 => {} token1", half, t1_out);
+
+    t0cli.transfer(&cca, pair, &(token0_amt - half));
+    t1cli.transfer(&cca, pair, &t1_out);
+
+    // **APPROVE BEFORE DEPOSIT**
+    let t0_bal = t0cli.balance(&cca);
+    let t1_bal = t1cli.balance(&cca);
+    t0cli.approve(&cca, pair, &t0_bal, &0);
+    t1cli.approve(&cca, pair, &t1_bal, &0);
+
+    let lp = pcli.deposit(&cca);
+    ensure!(e, lp > 0, FlashErr::Maw);
+    //log!(&e, "[JOIN CAMPAIGN] minted {} LP for user {:?}", lp, user);
+    Ok(lp)
+}
+
+/// Credits `lp` and its weight to `user`'s position in campaign `id`.
+/// `lp_held` is the part of `lp` this position escrows and pays back at
+/// claim; less than `lp` when the deposit is shared with other campaigns.
+fn credit_pos(
+    e: &Env,
+    id: u32,
+    c: &mut Campaign,
+    user: &Address,
+    lp: i128,
+    lp_held: i128,
+    token0_amt: i128,
+) {
+    let now = e.ledger().sequence();
+    /*
+        let w   = lp * (c.end_ledger-now) as i128 / c.duration as i128;
+
+        c.total_lp     += lp;
+        c.total_weight += w;
+
+        save_camp(&e,id,&c);
+    */
+    // --- Track user count for rank; a repeat deposit keeps its first rank ---
+    // (overwriting the position would strand the earlier LP, which is
+    // still counted in total_lp and held by this contract)
+    let key = upos_key(e, id, user);
+    let prev: Option<UserPos> = e.storage().instance().get(&key);
+    let rank = match &prev {
+        Some(p) => p.rank,
+        None => {
+            let ucnt_val = e
+                .storage()
+                .instance()
+                .get::<Val, u32>(&ucnt_key(e, id))
+                .unwrap_or(0);
+            let rank = ucnt_val + 1;
+            e.storage().instance().set(&ucnt_key(e, id), &rank);
+            add_user_camp(e, user, id);
+            set_rank_user(e, id, rank, user);
+            position::mint_event(e, user, position::token_id(id, rank));
+            rank
+        }
+    };
+
+    // --- Compute weights using rewards.rs ---
+    let contrib_weight = rewards::compute_contribution_weight(e, lp, c.target_lp);
+    let gamma = 2; // or configurable
+    let rank_weight = rewards::compute_rank_weight(e, rank, gamma);
+    let weight = rewards::compute_score(e, rank_weight, contrib_weight);
+
+    c.total_lp += lp;
+    c.total_weight += weight;
+    save_camp(e, id, c);
+
+    //let mut up:UserPos = e.storage().instance().get(&key).unwrap_or(UserPos{lp:0,weight:0});
+    //up.lp+=lp; up.weight+=w;
+    let up = match prev {
+        Some(p) => UserPos {
+            lp: p.lp + lp,
+            weight: p.weight + weight,
+            joined_ledger: p.joined_ledger,
+            rank,
+            deposit: p.deposit + token0_amt,
+            lp_held: p.lp_held + lp_held,
+        },
+        None => UserPos {
+            lp,
+            weight,
+            joined_ledger: now,
+            rank,
+            deposit: token0_amt,
+            lp_held,
+        },
+    };
+
+    e.storage().instance().set(&key, &up);
+    //log!(&e, "[JOIN CAMPAIGN] join(id {}) user {:?} lp {} weight {}", id, user, lp, w);
+}
+
+/// Single-campaign join: checks limits, zaps in and credits the position.
+fn join_pos(e: &Env, id: u32, token0_amt: i128, user: &Address) -> Result<(), FlashErr> {
+    let mut c = load_camp(e, id);
+    check_join(e, id, &c, user, token0_amt)?;
+    let lp = zap_in(e, &c.pair, token0_amt, user)?;
+    ensure!(e, fits_hard_cap(&c, lp), FlashErr::HardCapReached);
+    credit_pos(e, id, &mut c, user, lp, lp, token0_amt);
+    Ok(())
+}
+
+// -------------------------------------------------------------
+// Smart accounts
+// -------------------------------------------------------------
+/// Returns `user`'s smart account, deploying it with `user` as owner on
+/// first use. The salt is `sha256(xdr(user))`, so the address is fixed per
+/// user and only this contract can deploy there; the registry below is
+/// therefore the whole truth about which accounts exist.
+fn get_or_deploy_account(e: &Env, user: &Address, signer: Option<Signer>) -> Result<Address, FlashErr> {
+    if let Some(account) = get_account(e, user) {
+        return Ok(account);
+    }
+    // the account's first signer; ignored once it exists
+    let signer = signer.ok_or(FlashErr::AccountSignerRequired)?;
+    let config = get_core_config(e);
+    // passkey signers can't verify without it, and can't set it themselves
+    let rp_id = config.account_rp_id.ok_or(FlashErr::AccountRpIdNotSet)?;
+    let salt: BytesN<32> = e.crypto().sha256(&user.clone().to_xdr(e)).into();
+    let account = e
+        .deployer()
+        .with_current_contract(salt)
+        .deploy_v2(config.account_wasm, (user.clone(), signer, rp_id));
+    #[cfg(all(not(target_family = "wasm")))]
+    std::println!("[get_or_deploy_account] deployed {account:?} for {user:?}");
+    set_account(e, user, &account);
+    Ok(account)
+}
+
+// -------------------------------------------------------------
+// Claim helpers
+// -------------------------------------------------------------
+/// Sends `user`'s FLASH share and escrowed LP of an ended campaign to
+/// `recipient` and drops the position. `cut_bps` of the FLASH share, capped
+/// at `MAX_KEEPER_BPS`, is deducted from it and stays here for the caller to
+/// forward as a keeper bounty. Returns `(flash, lp, cut)`.
+fn pay_out(
+    e: &Env,
+    id: u32,
+    c: &Campaign,
+    user: &Address,
+    recipient: &Address,
+    cut_bps: u32,
+) -> Result<(i128, i128, i128), FlashErr> {
+    let key = upos_key(e, id, user);
+    let up: UserPos = e.storage().instance().get(&key).ok_or(FlashErr::NothingToClaim)?;
+    ensure!(e, up.weight > 0, FlashErr::NothingToClaim);
+    ensure!(e, cut_bps <= MAX_KEEPER_BPS, FlashErr::BpsOutOfRange);
+
+    let share = |pool: i128| {
+        pool.checked_mul(up.weight)
+            .map(|v| v / c.total_weight)
+            .ok_or(FlashErr::Math)
+    };
+    let base = share(c.reward_flash)?;
+    let bonus = if c.total_lp >= c.target_lp {
+        share(c.bonus_flash)?
+    } else {
+        0
+    };
+    let cut = (base + bonus) * cut_bps as i128 / MAX_BPS as i128;
+    let total = base + bonus - cut;
+    let config = get_core_config(e);
+    TokenClient::new(e, &config.flash).transfer(&e.current_contract_address(), recipient, &total);
+
+    if up.lp_held > 0 {
+        TokenClient::new(e, &c.pair).transfer(&e.current_contract_address(), recipient, &up.lp_held);
+    }
+
+    e.storage().instance().remove(&key);
+    remove_user_camp(e, user, id);
+    position::burn_event(e, user, position::token_id(id, up.rank));
+    Ok((total, up.lp_held, cut))
+}
+
+/// Hands position `token_id` from its owner `from` to `to`, merging it into
+/// a position `to` already holds in that campaign. Callers check auth.
+fn move_position(e: &Env, from: &Address, to: &Address, token_id: u64) -> Result<(), FlashErr> {
+    ensure!(
+        e,
+        position::owner_of(e, token_id) == Some(from.clone()),
+        FlashErr::NotPositionOwner
+    );
+    let (id, rank) = position::split(token_id);
+    let from_key = upos_key(e, id, from);
+    let up: UserPos = e.storage().instance().get(&from_key).ok_or(FlashErr::NotPositionOwner)?;
+    e.storage().instance().remove(&from_key);
+    remove_user_camp(e, from, id);
+    set_position_approval(e, token_id, from, None);
+    position::transfer_event(e, from, to, token_id);
+
+    let to_key = upos_key(e, id, to);
+    match e.storage().instance().get::<Val, UserPos>(&to_key) {
+        // `to` is already in the campaign: fold the two positions into the
+        // one with the earlier rank and burn the other token
+        Some(held) => {
+            let (keep, gone) = (rank.min(held.rank), rank.max(held.rank));
+            let merged = UserPos {
+                lp: held.lp + up.lp,
+                weight: held.weight + up.weight,
+                joined_ledger: held.joined_ledger.min(up.joined_ledger),
+                rank: keep,
+                deposit: held.deposit + up.deposit,
+                lp_held: held.lp_held + up.lp_held,
+            };
+            e.storage().instance().set(&to_key, &merged);
+            set_rank_user(e, id, keep, to);
+            remove_rank_user(e, id, gone);
+            position::burn_event(e, to, position::token_id(id, gone));
+        }
+        None => {
+            e.storage().instance().set(&to_key, &up);
+            set_rank_user(e, id, rank, to);
+            add_user_camp(e, to, id);
+        }
+    }
+    Ok(())
+}
+
+/// Batch variant of `claim`: reports instead of failing.
+fn try_claim(e: &Env, id: u32, user: &Address, recipient: &Address, cut_bps: u32) -> (ClaimResult, i128) {
+    let mut r = ClaimResult { id, status: ClaimStatus::NothingToClaim, flash: 0, lp: 0 };
+    let mut cut = 0;
+    if !e.storage().instance().has(&upos_key(e, id, user)) {
+        return (r, cut);
+    }
+    let c = load_camp(e, id);
+    if e.ledger().sequence() < c.end_ledger {
+        r.status = ClaimStatus::NotEnded;
+    } else {
+        match pay_out(e, id, &c, user, recipient, cut_bps) {
+            Ok((flash, lp, keep)) => {
+                r.status = ClaimStatus::Claimed;
+                r.flash = flash;
+                r.lp = lp;
+                cut = keep;
+            }
+            Err(FlashErr::NothingToClaim) => {}
+            Err(err) => r.status = ClaimStatus::Failed(err as u32),
+        }
+    }
+    (r, cut)
+}
+
 // -------------------------------------------------------------
 // Contract interface
 // -------------------------------------------------------------
@@ -207,45 +881,138 @@ pub trait Manager {
         unlock: u32,
         target_lp: i128,
         bonus_flash: i128,
+        allow_root: Option<BytesN<32>>,
+        limits: CampaignLimits,
         creator: Address,
     ) -> Result<u32, FlashErr>;
     fn ucnt_key(e: &Env, id: u32) -> Result<Val, FlashErr>;
-    fn join_campaign(e: Env, id: u32, token0_amt: i128, user: Address) -> Result<(), FlashErr>;
+    fn join_campaign(
+        e: Env,
+        id: u32,
+        token0_amt: i128,
+        user: Address,
+        proof: Option<AllowProof>,
+    ) -> Result<(), FlashErr>;
     fn join_campaign_with_account(
         e: Env,
         id: u32,
         token0_amt: i128,
         user: Address,
-        //account_wasm_hash: BytesN<32>,
+        proof: Option<AllowProof>,
+        signer: Option<Signer>,
     ) -> Result<(), FlashErr>;
+    /// Joins campaign `id` and lets the same deposit count toward every other
+    /// active campaign on its pair that the user can join without a proof.
+    /// Each credited campaign escrows an equal slice of the LP and returns it
+    /// at its own end. Returns the ids credited, `id` first.
+    fn join_all_on_pair(
+        e: Env,
+        id: u32,
+        token0_amt: i128,
+        user: Address,
+        proof: Option<AllowProof>,
+    ) -> Result<Vec<u32>, FlashErr>;
+    fn pair_campaigns(e: Env, pair: Address) -> Vec<u32>;
+    /// `(reward token, LP token)` campaign `id` pays out in.
+    fn payout_tokens(e: Env, id: u32) -> (Address, Address);
+    /// Adds `fee_usdc` to a running campaign through the same USDC -> FLASH
+    /// pipeline as `create_campaign` and optionally pushes its end back by
+    /// `extra_ledgers`; only the creator may extend. Returns the FLASH added
+    /// to the reward pool.
+    fn top_up_campaign(
+        e: Env,
+        id: u32,
+        fee_usdc: i128,
+        extra_ledgers: u32,
+        funder: Address,
+    ) -> Result<i128, FlashErr>;
+    fn funding(e: Env, id: u32, funder: Address) -> Funding;
+    /// Proposes a co-sponsored campaign. Sponsors `pledge` USDC for
+    /// `pledge_window` ledgers (at most ~30 days); the campaign opens once
+    /// `threshold_usdc` is met.
+    fn propose_campaign(
+        e: Env,
+        params: CampaignParams,
+        threshold_usdc: i128,
+        pledge_window: u32,
+        proposer: Address,
+    ) -> Result<u32, FlashErr>;
+    /// Escrows `amount` USDC. The pledge that meets the threshold activates
+    /// the campaign through the `create_campaign` fee split on the whole pot
+    /// and gets its id back. If that split fails the pledge reverts with its
+    /// error and the proposal stays open.
+    fn pledge(e: Env, pid: u32, amount: i128, sponsor: Address) -> Result<Option<u32>, FlashErr>;
+    /// Refunds a pledge once the deadline has passed without activation.
+    fn withdraw_pledge(e: Env, pid: u32, sponsor: Address) -> Result<i128, FlashErr>;
+    fn proposal(e: Env, pid: u32) -> Option<Proposal>;
+    fn pledge_of(e: Env, pid: u32, sponsor: Address) -> i128;
     fn compound(e: Env, id: u32) -> Result<(), FlashErr>;
     fn claim(e: Env, id: u32, user: Address) -> Result<(), FlashErr>;
+    /// Claims every ended campaign in `ids` for `user`, paying `recipient`.
+    /// Campaigns not ended yet or without a position are skipped.
+    fn claim_many(e: Env, ids: Vec<u32>, user: Address, recipient: Address) -> Vec<ClaimResult>;
+    /// `claim_many` over every campaign `user` has joined and not claimed.
+    fn claim_all(e: Env, user: Address) -> Vec<ClaimResult>;
+    /// Where `claim`, `claim_all` and `distribute` send `user`'s rewards and
+    /// LP; `None` resets it to `user`.
+    fn set_claim_recipient(e: Env, user: Address, recipient: Option<Address>);
+    /// Lets anyone page through an ended campaign with `distribute`, for
+    /// `bounty_bps` (at most `MAX_KEEPER_BPS`) of every payout. The bounty is
+    /// deducted from each user's FLASH share; their LP is paid in full.
+    /// Creator only, before the campaign ends.
+    fn enable_distribution(e: Env, id: u32, bounty_bps: u32, creator: Address) -> Result<(), FlashErr>;
+    /// Pays out participants `start..start + limit` in join order to their
+    /// claim recipients and sends the bounty to `keeper`.
+    fn distribute(
+        e: Env,
+        id: u32,
+        start: u32,
+        limit: u32,
+        keeper: Address,
+    ) -> Result<Vec<ClaimResult>, FlashErr>;
+    /// Moves the whole position `token_id` (LP, weight and rank) to `to`,
+    /// who can then claim it. If `to` already holds a position in that
+    /// campaign the two are merged under the earlier rank's token id and the
+    /// other token is burned. Smart accounts go through their own
+    /// `transfer_position`.
+    fn transfer_position(e: Env, from: Address, to: Address, token_id: u64) -> Result<(), FlashErr>;
+    fn owner_of(e: Env, token_id: u64) -> Option<Address>;
+    /// Lets `spender` move `token_id` with `transfer_position_from` until the
+    /// position changes hands; `None` withdraws the approval.
+    fn approve_position(
+        e: Env,
+        owner: Address,
+        spender: Option<Address>,
+        token_id: u64,
+    ) -> Result<(), FlashErr>;
+    fn get_approved(e: Env, token_id: u64) -> Option<Address>;
+    /// Lets `operator` move every position `owner` holds, now or later.
+    fn set_position_operator(e: Env, owner: Address, operator: Address, approved: bool);
+    fn is_position_operator(e: Env, owner: Address, operator: Address) -> bool;
+    /// `transfer_position` by an approved spender or operator of `from`,
+    /// e.g. a marketplace or escrow taking custody.
+    fn transfer_position_from(
+        e: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u64,
+    ) -> Result<(), FlashErr>;
+    /// Token ids of the open positions `owner` holds.
+    fn positions_of(e: Env, owner: Address) -> Vec<u64>;
 
     fn set_surplus_bps(e: Env, admin: Address, bps: u32) -> Result<(), FlashErr>;
     fn set_ttl(e: Env, admin: Address, threshold: u32, bump: u32) -> Result<(), FlashErr>;
 
+    fn set_account_wasm(e: Env, admin: Address, wasm_hash: BytesN<32>) -> Result<(), FlashErr>;
+    /// The WebAuthn relying party id (e.g. `flashpool.app`) new accounts
+    /// accept passkey assertions for. Required before the first account.
+    fn set_account_rp_id(e: Env, admin: Address, rp_id: Bytes) -> Result<(), FlashErr>;
+
     /// Checks if a user's smart account contract is already deployed.
-    /// Uses the same salt derivation as join_campaign_with_account.
-    fn account_exists(e: Env, user: Address) -> bool {
-        // Derive the deterministic salt from the user's address (XDR bytes, hashed)
-        let user_bytes = &user.clone().to_xdr(&e);
-        let user_salt: BytesN<32> = e.crypto().sha256(&user_bytes).into();
-        let account_wasm = account_contract::WASM;
-        let account_wasm_bytes = Bytes::from_slice(&e, account_wasm);
-        let account_wasm_hash: BytesN<32> = e.crypto().sha256(&account_wasm_bytes).into();
-        let deployer = e.deployer();
-        let account_address = deployer.with_current_contract(user_salt).deployed_address();
-        // Use the environment's is_contract_deployed if available (Soroban SDK >= 22.0.0)
-        #[cfg(feature = "testutils")] // For native tests, always return true for deployed contracts
-        {
-            return e.is_contract_deployed(&account_address);
-        }
-        #[cfg(not(feature = "testutils"))]
-        {
-            // On-chain, this will return true if the contract is deployed
-            e.is_contract_deployed(&account_address)
-        }
-    }
+    fn account_exists(e: Env, user: Address) -> bool;
+    /// The user's smart account, if deployed.
+    fn account_of(e: Env, user: Address) -> Option<Address>;
 }
 
 #[contract]
@@ -401,6 +1168,10 @@ impl Manager for FlashCampaignManager {
             surplus_bps: DEFAULT_SURPLUS_BPS,
             ttl_thresh: DEFAULT_TTL_THRESH,
             ttl_bump: DEFAULT_TTL_BUMP,
+            account_wasm: e
+                .deployer()
+                .upload_contract_wasm(Bytes::from_slice(&e, account_contract::WASM)),
+            account_rp_id: None,
         };
 
         set_core_config(&e, &config);
@@ -421,405 +1192,413 @@ impl Manager for FlashCampaignManager {
         unlock: u32,
         target_lp: i128,
         bonus_flash: i128,
+        allow_root: Option<BytesN<32>>,
+        limits: CampaignLimits,
         creator: Address,
     ) -> Result<u32, FlashErr> {
         
         #[cfg(all(not(target_family = "wasm")))]
         std::println!("── [FCM - CREATE_CAMPAIGN] ──");
+        check_limits(&e, &limits, target_lp)?;
+        ensure!(&e, unlock <= MAX_DURATION, FlashErr::DurationTooLong);
         let cca = &e.current_contract_address();
-        if let Some(info) = get_active_campaign_for_pair(&e, &target_pool) {
-            ensure!(
-                &e,
-                info.end_ledger <= e.ledger().sequence(),
-                FlashErr::CampaignActiveForPair
-            );
-        }
         bump(&e);
         creator.require_auth();
         // Load config struct
-        let mut config = get_core_config(&e);
+        let config = get_core_config(&e);
 
         let core_pair_address = config
             .core_pair
             .clone()
            .ok_or(FlashErr::NoCorePair)?;
-        let core_pair = pair::Client::new(&e, &core_pair_address);
-
         let flash_address = config.flash.clone();
-        let usdc_address = config.usdc.clone();
-        let surplus_bps = config.surplus_bps;
-        ensure!(&e, surplus_bps < MAX_BPS, FlashErr::BpsOutOfRange);
         let balance_flash_start = TokenClient::new(&e, &flash_address).balance(&cca);
         let pair_balance_start = TokenClient::new(&e, &flash_address).balance(&core_pair_address);
-        let usd_cli = TokenClient::new(&e, &usdc_address);
         #[cfg(all(not(target_family = "wasm")))]
         std::println!(
             "    {creator:?} creating campaign with {:.7} USDC",
             (fee_usdc / 10i128.pow(7)) as f64
         );
 
-        // Take all USDC from creator
-        usd_cli.transfer(&creator, &cca, &fee_usdc);
-
-        let (r0, r1) = core_pair.get_reserves();
-        let (t0, t1) = o_t_c(&e, &flash_address, &usdc_address);
-        let t0_addr = t0.address.clone();
-        let t1_addr = t1.address.clone();
-        #[cfg(all(not(target_family = "wasm")))]
-        std::println!(
-            "    Raw pair reserves: {:?}={:.7}, {:?}={:.7}",
-            t0_addr, (r0 as f64) * 1e-7,
-            t1_addr, (r1 as f64) * 1e-7
-        );
-        // Map reserves so reserve_usdc_before / reserve_flash_before are correct
-        let (reserve_usdc_before, reserve_flash_before) =
-            if t0_addr == usdc_address && t1_addr == flash_address {
-                (r0, r1)
-            } else if t0_addr == flash_address && t1_addr == usdc_address {
-                (r1, r0)
-            } else {
-                return Err(FlashErr::InvalidToken);
-            };
-#[cfg(all(not(target_family = "wasm")))]
-        std::println!(
-            "    Pair reserves before (USDC→FLASH):\n        USDC={:.7}\n        FLASH={:.7}",
-            (reserve_usdc_before as f64) * 1e-7,
-            (reserve_flash_before as f64) * 1e-7
-        );
-
-        
-        // Fee split
-        let s_min = int_sqrt(
-            (reserve_usdc_before as u128) * (reserve_usdc_before as u128 + fee_usdc as u128),
-        ) as i128
-            - reserve_usdc_before;
+        let reward_flash = fee_to_reward(&e, &config, fee_usdc, Some(&creator))?;
+        //total lp is the total lp that was minted by users joining the pool, at this point it is zero.  same with stake lp... it should get updated later when users join the pool... however it can't seem to find it.  maybe we need current lp also, finally i am not sure what weight is supposed to be right now. i believe it's to do with the weight of everyone adding liquidity we'll have to figure that out later too
+        let params = CampaignParams {
+            pair: target_pool.clone(),
+            unlock,
+            target_lp,
+            bonus_flash,
+            allow_root,
+            limits,
+        };
+        let id = open_campaign(&e, &params, reward_flash, &creator);
+        add_funding(&e, id, &creator, fee_usdc, reward_flash);
 
         #[cfg(all(not(target_family = "wasm")))]
         {
-            let smin_log = s_min as f64 * 1e-7;
-            let usdc_fee_log = fee_usdc as f64 * 1e-7;
+            std::println!("Campaign created:\n    id={id}\n    target_lp={target_lp}\n   pair={target_pool:?}\n    reward_flash={reward_flash}\n    bonus_flash={bonus_flash}");
+            let camp = load_camp(&e, id);
             std::println!(
-                "    s_min={smin_log}, surplus_bps={surplus_bps}, fee_usdc={usdc_fee_log}"
-            );
-        }
-        let swap_amount = (s_min
-            + (fee_usdc as i128 * surplus_bps as i128) as i128 / MAX_BPS as i128)
-            .min(fee_usdc);
-        let usdc_liq = fee_usdc - swap_amount;
-        #[cfg(not(target_family = "wasm"))] {
-            let total_amt = swap_amount + usdc_liq;
+        "[create_campaign] Campaign from storage:\n{id}\n    pair={:?}\n    duration={}\n    end_ledger={}\n    target_lp={}\n    total_lp={:.7} \n    total_weight={}\n    reward_flash={:.7}\n    bonus_flash={:.7}\n    stake_lp={:.7}",
+        camp.pair, camp.duration, camp.end_ledger, camp.target_lp, camp.total_lp, camp.total_weight as f64, camp.reward_flash as f64 * 1e-7, camp.bonus_flash as f64*1e-7, camp.stake_lp as f64 * 1e-7
+    );
+    let contract_bal = TokenClient::new(&e, &flash_address).balance(&e.current_contract_address());
+    let pair_bal = TokenClient::new(&e, &flash_address).balance(&core_pair_address);
+    let contractcirculatingbalance = (balance_flash_start - contract_bal) as f64 * 1e-7;
+    let pairbalanceafter = (pair_balance_start - pair_bal) as f64 * 1e-7;
             std::println!(
-                "    Fee Split Calculation:\n        fee_usdc={:.7}\n        TotalAmount={:.7}\n        s_min={:.7}\n        surplus_bps={}\n        swap_amount={:.7} USDC\n        usdc_liq={:.7} USDC",
-                (fee_usdc as f64)   * 1e-7,
-                (total_amt as f64)  * 1e-7,
-                (s_min as f64)      * 1e-7,
-                surplus_bps,
-                (swap_amount as f64)* 1e-7,
-                (usdc_liq as f64)   * 1e-7
-            );
+                "[create_campaign] CoreConfig stored:,\n    balance_flash_start{:7}\n    contract_bal: {:.7}\n    pair_balance_start: {:.7}\n    pair_bal: {:.7}\n    pair after: {:.7}\n    cont after {:.7}", balance_flash_start, contract_bal, pair_balance_start, pair_bal, pairbalanceafter, contractcirculatingbalance);
         }
-                
-        let is_t0_usdc = t0_addr == usdc_address;
-        // swap: send USDC_in then instruct pair.swap
-        usd_cli.transfer(&cca, &core_pair_address, &swap_amount);
-        
-         let flash_out = c_s_o1(swap_amount, reserve_usdc_before, reserve_flash_before);
+        Ok(id)
+    }
 
-        //let flash_out = c_s_o(swap_amount, usdc_reserves_before, flash_reserves_before);
+    fn ucnt_key(e: &Env, id: u32) -> Result<Val, FlashErr> {
+        Ok((PREFIX_UCNT, id).into_val(e))
+    }
 
-        ensure!(&e, flash_out > 0 && flash_out < reserve_flash_before, FlashErr::Math);
+    // ------------------------------------------- join_campaign ---
+    fn join_campaign(
+        e: Env,
+        id: u32,
+        token0_amt: i128,
+        user: Address,
+        proof: Option<AllowProof>,
+    ) -> Result<(), FlashErr> {
+        bump(&e);
+        user.require_auth();
+        ensure!(&e, token0_amt > 0, FlashErr::Maq);
+        check_allowlist(&e, id, &user, token0_amt, proof)?;
+        join_pos(&e, id, token0_amt, &user)
+    }
 
-        //todo: make this more efficient, see utils::line5
-        let (out_0, out_1) = if is_t0_usdc {
-            (0, flash_out) // token0 = USDC, token1 = FLASH
-        } else {
-            (flash_out, 0) // token0 = FLASH, token1 = USDC
-        };
-        #[cfg(all(not(target_family = "wasm")))]
-        std::println!("    Swapping\n        swap_amount={:.7} USDC\n        flash_out={:.7}\n        out0={out_0}\n        out1={out_1}", (swap_amount as f64 * 1e-7) as f64, (flash_out as f64 * 1e-7) as f64);
+    fn join_campaign_with_account(
+        e: Env,
+        id: u32,
+        token0_amt: i128,
+        user: Address,
+        proof: Option<AllowProof>,
+        signer: Option<Signer>,
+    ) -> Result<(), FlashErr> {
+        bump(&e);
+        user.require_auth();
+        ensure!(&e, token0_amt > 0, FlashErr::Maq);
+        // the allowlist snapshot is of users, not of their smart accounts
+        check_allowlist(&e, id, &user, token0_amt, proof)?;
+        // 1. The user's smart account, deployed on first use with `signer`
+        let account_address = get_or_deploy_account(&e, &user, signer)?;
+
+        // 2. Zap the user's funds straight in; the position belongs to the
+        //    smart account, which claims it later through its own entrypoints.
+        //    (Routing the deposit through the account would need it to call
+        //    back into this contract, which Soroban does not allow.)
+        let mut c = load_camp(&e, id);
+        check_join(&e, id, &c, &account_address, token0_amt)?;
+        let lp = zap_in(&e, &c.pair, token0_amt, &user)?;
+        ensure!(&e, fits_hard_cap(&c, lp), FlashErr::HardCapReached);
+        credit_pos(&e, id, &mut c, &account_address, lp, lp, token0_amt);
+        Ok(())
+    }
 
-        core_pair.swap(&out_0, &out_1, &e.current_contract_address());
+    // ---------------------------------------- join_all_on_pair ---
+    fn join_all_on_pair(
+        e: Env,
+        id: u32,
+        token0_amt: i128,
+        user: Address,
+        proof: Option<AllowProof>,
+    ) -> Result<Vec<u32>, FlashErr> {
+        bump(&e);
+        user.require_auth();
+        ensure!(&e, token0_amt > 0, FlashErr::Maq);
+        check_allowlist(&e, id, &user, token0_amt, proof)?;
 
-        // After swap
-        let usdc_reserves_post_swap = reserve_usdc_before + swap_amount;
-        let flash_reserves_post_swap = reserve_flash_before - flash_out;
-        #[cfg(all(not(target_family = "wasm")))]
-        {
-        let (pr1, pr0) = core_pair.get_reserves();
-        std::println!(
-            "Post-swap reserves: \n   FLASH={:.7}\n    USDC={:.7}\n   pr0={:.7}\n   pr1={:.7}", flash_reserves_post_swap as f64 * 1e-7, usdc_reserves_post_swap as f64 * 1e-7, pr0 as f64 * 1e-7, pr1 as f64 * 1e-7
-        );
+        let c = load_camp(&e, id);
+        check_join(&e, id, &c, &user, token0_amt)?;
 
+        // other live campaigns this deposit may also count toward; gated ones
+        // are skipped since a proof only covers one campaign
+        let now = e.ledger().sequence();
+        let mut others = Vec::new(&e);
+        for info in get_pair_campaigns(&e, &c.pair).iter() {
+            if info.campaign_id == id || info.end_ledger <= now {
+                continue;
+            }
+            let other = load_camp(&e, info.campaign_id);
+            if other.allow_root.is_none()
+                && check_join(&e, info.campaign_id, &other, &user, token0_amt).is_ok()
+            {
+                others.push_back(info.campaign_id);
+            }
         }
-       
-        // Flash needed for add_liq
-        ensure!(&e, usdc_reserves_post_swap > 0, FlashErr::Math);
 
-        let flash_needed = usdc_liq
-            .checked_mul(flash_reserves_post_swap)
-            .unwrap()
-            .checked_div(usdc_reserves_post_swap)
-            .unwrap();
+        let lp = zap_in(&e, &c.pair, token0_amt, &user)?;
+        ensure!(&e, fits_hard_cap(&c, lp), FlashErr::HardCapReached);
+
+        let mut ids = Vec::from_array(&e, [id]);
+        for oid in others.iter() {
+            if fits_hard_cap(&load_camp(&e, oid), lp) {
+                ids.push_back(oid);
+            }
+        }
 
+        // each credited campaign escrows an equal slice of the LP and pays it
+        // back at its own end, so no claim depends on another campaign's end
+        // (which a top-up may move); dust goes to the last slice
+        let n = ids.len() as i128;
+        for (i, cid) in ids.iter().enumerate() {
+            let mut cc = load_camp(&e, cid);
+            let held = if i as i128 + 1 == n { lp - lp / n * (n - 1) } else { lp / n };
+            credit_pos(&e, cid, &mut cc, &user, lp, held, token0_amt);
+        }
         #[cfg(all(not(target_family = "wasm")))]
-        {
-            let flash_needed_pretty = flash_needed as f64 * 1e-7;
+        std::println!("[join_all_on_pair] lp {} credited to {:?}, escrow split {} ways", lp, ids, n);
+        Ok(ids)
+    }
 
-            let usdc_liq_amount_pretty = usdc_liq as f64 * 1e-7;
-            std::println!("Calculated flash_needed={flash_needed_pretty} for usdc_liq={usdc_liq_amount_pretty} USDC (flash_reserves_post_swap={flash_reserves_post_swap}, usdc_reserves_post_swap={usdc_reserves_post_swap})");
+    fn pair_campaigns(e: Env, pair: Address) -> Vec<u32> {
+        let now = e.ledger().sequence();
+        let mut ids = Vec::new(&e);
+        for info in get_pair_campaigns(&e, &pair).iter() {
+            if info.end_ledger > now {
+                ids.push_back(info.campaign_id);
+            }
+        }
+        ids
+    }
 
-            let (a0, a1) = if is_t0_usdc {
-                (flash_needed, usdc_liq)
-            } else {
-                (usdc_liq, flash_needed)
-            };
+    fn payout_tokens(e: Env, id: u32) -> (Address, Address) {
+        (get_core_config(&e).flash, load_camp(&e, id).pair)
+    }
 
-            std::println!(
-                "Transferred: token_0={:?} amount_0={:.7}, token_1={:?} amount_1={:.7}",
-                t0_addr,
-                (a0 as f64 * 1e-7) as f64,
-                t1_addr,
-                (a1 as f64 * 1e-7) as f64
-            );
+    // -------------------------------------------- top_up_campaign --
+    fn top_up_campaign(
+        e: Env,
+        id: u32,
+        fee_usdc: i128,
+        extra_ledgers: u32,
+        funder: Address,
+    ) -> Result<i128, FlashErr> {
+        bump(&e);
+        funder.require_auth();
+        ensure!(&e, fee_usdc >= 0, FlashErr::Math);
+        let mut c = load_camp(&e, id);
+        ensure!(&e, e.ledger().sequence() < c.end_ledger, FlashErr::CampaignEnded);
+        // anyone may fund, but only the creator moves the end
+        ensure!(&e, extra_ledgers == 0 || funder == c.creator, FlashErr::NotCreator);
 
-            // Log contract's pool-related token balances before deposit
-            let t0_balance = t0.balance(&e.current_contract_address());
-            let t1_balance = t1.balance(&e.current_contract_address());
-            std::println!(
-                "Contract balances before xfer: \n    token_0={:?}={:.7}\n    token_1={:?}={:.7}",
-                t0_addr,
-                (t0_balance as f64 * 1e-7) as f64,
-                t1_addr,
-                (t1_balance as f64 * 1e-7) as f64
-            );
+        let mut added = 0;
+        if fee_usdc > 0 {
+            let config = get_core_config(&e);
+            added = fee_to_reward(&e, &config, fee_usdc, Some(&funder))?;
+            c.reward_flash += added;
+            add_funding(&e, id, &funder, fee_usdc, added);
         }
+        if extra_ledgers > 0 {
+            let duration = c
+                .duration
+                .checked_add(extra_ledgers)
+                .ok_or(FlashErr::DurationTooLong)?;
+            ensure!(&e, duration <= MAX_DURATION, FlashErr::DurationTooLong);
+            c.duration = duration;
+            c.end_ledger += extra_ledgers;
+            set_pair_campaign_end(&e, &c.pair, id, c.end_ledger);
+        }
+        save_camp(&e, id, &c);
 
-        let lpm = d_t_p(
-            &e,
-            &core_pair_address,
-            &flash_address,
-            &usdc_address,
-            flash_needed,
-            usdc_liq,
-        );
-        ensure!(&e, lpm > 0, FlashErr::Math);
-        #[cfg(all(not(target_family = "wasm")))]
-        std::println!("Deposit complete. flash/usdc lp minted={:.7}", lpm as f64 * 1e-7);
-
-        // 7. reward pool calc
-        let actual_usdc = TokenClient::new(&e, &usdc_address).balance(&core_pair_address)
-            - usdc_reserves_post_swap;
-        let actual_flash = TokenClient::new(&e, &flash_address).balance(&core_pair_address)
-            - flash_reserves_post_swap;
         #[cfg(all(not(target_family = "wasm")))]
         std::println!(
-            "Actual deposit amounts:\n    actual_flsh={:.7}\n    actual_usdc={:.7}",
-            (actual_flash as f64 * 1e-7) as f64,
-            (actual_usdc as f64 * 1e-7) as f64
-        );
-        // compare and verify reserves and balances and math is correct below.
-        let ru1 = usdc_reserves_post_swap + actual_usdc;
-        let rf1 = flash_reserves_post_swap + actual_flash;
-        let root = int_sqrt(
-            (ru1 as u128 * rf1 as u128 * reserve_flash_before as u128)
-                / (reserve_usdc_before as u128),
+            "[top_up_campaign] id={id} funder={funder:?} +{:.7} FLASH, end_ledger={}",
+            added as f64 * 1e-7,
+            c.end_ledger
         );
-        let x_max = if root > rf1 as u128 {
-            (root - rf1 as u128) as i128
-        } else {
-            0
-        };
+        Ok(added)
+    }
 
-        let surplus = flash_out + actual_flash - flash_needed;
-        let reward_flash = surplus.min(x_max);
-        #[cfg(all(not(target_family = "wasm")))]{
-        std::println!("Reward pool calculation: ru1={ru1}, rf1={rf1}, root={root}, x_max={x_max}, surplus={surplus}, reward_flash={reward_flash}");
-        std::println!(
-            "Reward pool calculation:\n    ru1={:.7}\n    rf1={:.7}\n    root={:.7}\n    x_max={:.7}\n    surplus={:.7}\n    reward_flash={:.7}",
-            (ru1 as f64 * 1e-7) as f64,
-            (rf1 as f64 * 1e-7) as f64,
-            (root as f64 * 1e-7) as f64,
-            (x_max as f64 * 1e-7) as f64,
-            (surplus as f64 * 1e-7) as f64,
-            (reward_flash as f64 * 1e-7) as f64
+    fn funding(e: Env, id: u32, funder: Address) -> Funding {
+        get_funding(&e, id, &funder)
+    }
+
+    // ---------------------------------------------------- pledges --
+    fn propose_campaign(
+        e: Env,
+        params: CampaignParams,
+        threshold_usdc: i128,
+        pledge_window: u32,
+        proposer: Address,
+    ) -> Result<u32, FlashErr> {
+        bump(&e);
+        proposer.require_auth();
+        check_limits(&e, &params.limits, params.target_lp)?;
+        ensure!(&e, params.unlock <= MAX_DURATION, FlashErr::DurationTooLong);
+        ensure!(&e, threshold_usdc > 0, FlashErr::Math);
+        ensure!(
+            &e,
+            pledge_window > 0 && pledge_window <= MAX_PLEDGE_WINDOW,
+            FlashErr::InvalidPledgeWindow
         );
-        }
-        //total lp is the total lp that was minted by users joining the pool, at this point it is zero.  same with stake lp... it should get updated later when users join the pool... however it can't seem to find it.  maybe we need current lp also, finally i am not sure what weight is supposed to be right now. i believe it's to do with the weight of everyone adding liquidity we'll have to figure that out later too
-        let id = config.next + 1;
-        config.next = id;
-        set_core_config(&e, &config);
 
-        save_camp(
+        let pid = next_prop_id(&e);
+        save_prop(
             &e,
-            id,
-            &Campaign {
-                pair: target_pool.clone(),
-                duration: unlock,
-                end_ledger: e.ledger().sequence() + unlock,
-                target_lp,
-                total_lp: 0,
-                total_weight: 0,
-                reward_flash,
-                bonus_flash,
-                stake_lp: 0,
+            pid,
+            &Proposal {
+                params,
+                proposer,
+                threshold_usdc,
+                deadline: e.ledger().sequence() + pledge_window,
+                pledged: 0,
+                sponsors: Vec::new(&e),
+                campaign_id: None,
             },
         );
-        let info = ActiveCampaignInfo {
-            campaign_id: id,
-            end_ledger: e.ledger().sequence() + unlock,
-        };
-        set_active_campaign_for_pair(&e, &target_pool, &info);
+        Ok(pid)
+    }
 
-        #[cfg(all(not(target_family = "wasm")))]
-        {
-            std::println!("Campaign created:\n    id={id}\n    target_lp={target_lp}\n   pair={target_pool:?}\n    reward_flash={reward_flash}\n    bonus_flash={bonus_flash}");
-            let camp = load_camp(&e, id);
-            std::println!(
-        "[create_campaign] Campaign from storage:\n{id}\n    pair={:?}\n    duration={}\n    end_ledger={}\n    target_lp={}\n    total_lp={:.7} \n    total_weight={}\n    reward_flash={:.7}\n    bonus_flash={:.7}\n    stake_lp={:.7}",
-        camp.pair, camp.duration, camp.end_ledger, camp.target_lp, camp.total_lp, camp.total_weight as f64, camp.reward_flash as f64 * 1e-7, camp.bonus_flash as f64*1e-7, camp.stake_lp as f64 * 1e-7
-    );
-    let contract_bal = TokenClient::new(&e, &flash_address).balance(&e.current_contract_address());
-    let pair_bal = TokenClient::new(&e, &flash_address).balance(&core_pair_address);
-    let contractcirculatingbalance = (balance_flash_start - contract_bal) as f64 * 1e-7;
-    let pairbalanceafter = (pair_balance_start - pair_bal) as f64 * 1e-7;
+    fn pledge(e: Env, pid: u32, amount: i128, sponsor: Address) -> Result<Option<u32>, FlashErr> {
+        bump(&e);
+        sponsor.require_auth();
+        ensure!(&e, amount > 0, FlashErr::Math);
+        let mut p = load_prop(&e, pid).ok_or(FlashErr::NoSuchProposal)?;
+        ensure!(
+            &e,
+            p.campaign_id.is_none() && e.ledger().sequence() <= p.deadline,
+            FlashErr::PledgeClosed
+        );
+
+        let config = get_core_config(&e);
+        TokenClient::new(&e, &config.usdc).transfer(&sponsor, &e.current_contract_address(), &amount);
+        let before = get_pledge(&e, pid, &sponsor);
+        if before == 0 {
+            p.sponsors.push_back(sponsor.clone());
+        }
+        set_pledge(&e, pid, &sponsor, before + amount);
+        p.pledged += amount;
+
+        if p.pledged >= p.threshold_usdc {
+            let reward_flash = fee_to_reward(&e, &config, p.pledged, None)?;
+            let id = open_campaign(&e, &p.params, reward_flash, &p.proposer);
+            // attribute the reward pool pro rata, rounding dust to the last sponsor
+            let mut left = reward_flash;
+            let n = p.sponsors.len();
+            for (i, s) in p.sponsors.iter().enumerate() {
+                let usdc = get_pledge(&e, pid, &s);
+                let flash = if i as u32 + 1 == n {
+                    left
+                } else {
+                    reward_flash * usdc / p.pledged
+                };
+                left -= flash;
+                add_funding(&e, id, &s, usdc, flash);
+            }
+            p.campaign_id = Some(id);
+            #[cfg(all(not(target_family = "wasm")))]
             std::println!(
-                "[create_campaign] CoreConfig stored:,\n    balance_flash_start{:7}\n    contract_bal: {:.7}\n    pair_balance_start: {:.7}\n    pair_bal: {:.7}\n    pair after: {:.7}\n    cont after {:.7}", balance_flash_start, contract_bal, pair_balance_start, pair_bal, pairbalanceafter, contractcirculatingbalance);
+                "[pledge] proposal {pid} activated as campaign {id} with {:.7} USDC from {n} sponsors",
+                p.pledged as f64 * 1e-7
+            );
         }
-        Ok(id)
-    }
-
-    fn ucnt_key(e: &Env, id: u32) -> Result<Val, FlashErr> {
-        Ok((PREFIX_UCNT, id).into_val(e))
+        save_prop(&e, pid, &p);
+        Ok(p.campaign_id)
     }
 
-    // ------------------------------------------- join_campaign ---
-    fn join_campaign(e: Env, id: u32, token0_amt: i128, user: Address) -> Result<(), FlashErr> {
+    fn withdraw_pledge(e: Env, pid: u32, sponsor: Address) -> Result<i128, FlashErr> {
         bump(&e);
-        user.require_auth();
-        ensure!(&e, token0_amt > 0, FlashErr::Maq);
-
-        let mut c = load_camp(&e, id);
-        let pcli = pair::Client::new(&e, &c.pair);
-        let t0 = pcli.token_0();
-        let t1 = pcli.token_1();
-        let t0cli = TokenClient::new(&e, &t0);
-        let t1cli = TokenClient::new(&e, &t1);
-        t0cli.transfer(&user, &e.current_contract_address(), &token0_amt);
-        //log!(&e, "[JOIN CAMPAIGN] deposit {} of token0 from {:?}", token0_amt, user);
-
-        let half = token0_amt / 2;
-        t0cli.transfer(&e.current_contract_address(), &c.pair, &half);
-        let (r0, r1) = pcli.get_reserves();
-        let t1_out = r1
-            .checked_mul(half)
-            .unwrap()
-            .checked_div(r0 + half)
-            .unwrap();
-        let (o0, o1) = if t0 < t1 { (0, t1_out) } else { (t1_out, 0) };
-        pcli.swap(&o0, &o1, &e.current_contract_address());
-        //log!(&e, "[JOIN CAMPAIGN] swap_half: {} token0 => {} token1", half, t1_out);
-
-        t0cli.transfer(&e.current_contract_address(), &c.pair, &(token0_amt - half));
-        t1cli.transfer(&e.current_contract_address(), &c.pair, &t1_out);
-
-        // **APPROVE BEFORE DEPOSIT**
-        let t0_bal = t0cli.balance(&e.current_contract_address());
-        let t1_bal = t1cli.balance(&e.current_contract_address());
-        t0cli.approve(&e.current_contract_address(), &c.pair, &t0_bal, &0);
-        t1cli.approve(&e.current_contract_address(), &c.pair, &t1_bal, &0);
-
-        let lp = pcli.deposit(&e.current_contract_address());
-        ensure!(&e, lp > 0, FlashErr::Maw);
+        sponsor.require_auth();
+        let mut p = load_prop(&e, pid).ok_or(FlashErr::NoSuchProposal)?;
+        ensure!(
+            &e,
+            p.campaign_id.is_none() && e.ledger().sequence() > p.deadline,
+            FlashErr::PledgeLocked
+        );
+        let amount = get_pledge(&e, pid, &sponsor);
+        ensure!(&e, amount > 0, FlashErr::NothingToClaim);
+
+        set_pledge(&e, pid, &sponsor, 0);
+        p.pledged -= amount;
+        save_prop(&e, pid, &p);
+        let usdc = get_core_config(&e).usdc;
+        TokenClient::new(&e, &usdc).transfer(&e.current_contract_address(), &sponsor, &amount);
+        Ok(amount)
+    }
 
-        //log!(&e, "[JOIN CAMPAIGN] minted {} LP for user {:?}", lp, user);
+    fn proposal(e: Env, pid: u32) -> Option<Proposal> {
+        load_prop(&e, pid)
+    }
 
-        let now = e.ledger().sequence();
-        /*
-            let w   = lp * (c.end_ledger-now) as i128 / c.duration as i128;
+    fn pledge_of(e: Env, pid: u32, sponsor: Address) -> i128 {
+        get_pledge(&e, pid, &sponsor)
+    }
 
-            c.total_lp     += lp;
-            c.total_weight += w;
+    // -------------------------------------------------- positions --
+    fn transfer_position(e: Env, from: Address, to: Address, token_id: u64) -> Result<(), FlashErr> {
+        bump(&e);
+        from.require_auth();
+        move_position(&e, &from, &to, token_id)
+    }
 
-            save_camp(&e,id,&c);
-        */
-        // --- Track user count for rank ---
-        let ucnt_val = e
-            .storage()
-            .instance()
-            .get::<Val, u32>(&ucnt_key(&e, id))
-            .unwrap_or(0);
-        let rank = ucnt_val + 1;
-        e.storage().instance().set(&ucnt_key(&e, id), &rank);
+    fn owner_of(e: Env, token_id: u64) -> Option<Address> {
+        position::owner_of(&e, token_id)
+    }
 
-        // --- Compute weights using rewards.rs ---
-        let contrib_weight = rewards::compute_contribution_weight(&e, lp, c.target_lp);
-        let gamma = 2; // or configurable
-        let rank_weight = rewards::compute_rank_weight(&e, rank, gamma);
-        let weight = rewards::compute_score(&e, rank_weight, contrib_weight);
+    fn approve_position(
+        e: Env,
+        owner: Address,
+        spender: Option<Address>,
+        token_id: u64,
+    ) -> Result<(), FlashErr> {
+        bump(&e);
+        owner.require_auth();
+        ensure!(
+            &e,
+            position::owner_of(&e, token_id) == Some(owner.clone()),
+            FlashErr::NotPositionOwner
+        );
+        set_position_approval(&e, token_id, &owner, spender.clone());
+        position::approve_event(&e, &owner, &spender, token_id);
+        Ok(())
+    }
 
-        c.total_lp += lp;
-        c.total_weight += weight;
-        save_camp(&e, id, &c);
+    fn get_approved(e: Env, token_id: u64) -> Option<Address> {
+        let owner = position::owner_of(&e, token_id)?;
+        get_position_approval(&e, token_id, &owner)
+    }
 
-        let key = upos_key(&e, id, &user);
-        //let mut up:UserPos = e.storage().instance().get(&key).unwrap_or(UserPos{lp:0,weight:0});
-        //up.lp+=lp; up.weight+=w;
-        let up = UserPos {
-            lp,
-            weight,
-            joined_ledger: now,
-            rank,
-        };
+    fn set_position_operator(e: Env, owner: Address, operator: Address, approved: bool) {
+        bump(&e);
+        owner.require_auth();
+        set_operator(&e, &owner, &operator, approved);
+        position::operator_event(&e, &owner, &operator, approved);
+    }
 
-        e.storage().instance().set(&key, &up);
-        Ok(())
-        //log!(&e, "[JOIN CAMPAIGN] join(id {}) user {:?} lp {} weight {}", id, user, lp, w);
+    fn is_position_operator(e: Env, owner: Address, operator: Address) -> bool {
+        is_operator(&e, &owner, &operator)
     }
 
-    fn join_campaign_with_account(
+    fn transfer_position_from(
         e: Env,
-        id: u32,
-        token0_amt: i128,
-        user: Address,
-        //account_wasm_hash: BytesN<32>, //this shouldn't be passed from the user.
+        spender: Address,
+        from: Address,
+        to: Address,
+        token_id: u64,
     ) -> Result<(), FlashErr> {
         bump(&e);
-        // 1. Derive smart account address: hash the user's Address bytes for deterministic salt
-        //let mut user_bytes_opt_1 = Bytes::new(&e);
-       // user_bytes_opt_1.append(&user.clone().to_xdr(&e));
-        //let salt: BytesN<32> = e.crypto().sha256(&user_bytes_opt_1).into();
-        let account_wasm = account_contract::WASM;
-         #[cfg(all(not(target_family = "wasm")))]
-        std::println!("join - 1");       
-        let account_wasm_bytes = Bytes::from_slice(&e, account_wasm);
-         #[cfg(all(not(target_family = "wasm")))]
-        std::println!("join - 2");      
-        let account_wasm_hash: BytesN<32> = e.crypto().sha256(&account_wasm_bytes).into(); 
-#[cfg(all(not(target_family = "wasm")))]
-        std::println!("join - 3");   
-        let user_bytes = &user.clone().to_xdr(&e);
-        #[cfg(all(not(target_family = "wasm")))]
-        std::println!("join - 4");   
-        //let user_bytes_n: BytesN<32> = BytesN::from_xdr(&e, &user_bytes_2).unwrap();
-        let user_salt: BytesN<32> = e.crypto().sha256(&user_bytes).into();
-        #[cfg(all(not(target_family = "wasm")))]
-        std::println!("[join_campaign_with_account] salt: {:?}", user_salt);        
-        let deployer = e.deployer();
-        // Use deploy_v2 (non-deprecated)
-        let account_address = deployer.with_current_contract(user_salt.clone()).deploy_v2(account_wasm_hash.clone(), ());
-
-        // 2. Transfer funds from user to smart account
-        let c = load_camp(&e, id);
-        let pcli = pair::Client::new(&e, &c.pair);
-        let t0 = pcli.token_0();
-        let t0cli = TokenClient::new(&e, &t0);
-        t0cli.transfer(&user, &account_address, &token0_amt);
-
-        // 3. Call deposit on smart account to transfer funds to manager
-        account_contract::Client::new(&e, &account_address).deposit(&user, &t0, &token0_amt);
+        spender.require_auth();
+        ensure!(
+            &e,
+            get_position_approval(&e, token_id, &from) == Some(spender.clone())
+                || is_operator(&e, &from, &spender),
+            FlashErr::NotApproved
+        );
+        move_position(&e, &from, &to, token_id)
+    }
 
-        // 4. Call join_campaign as the smart account
-        Self::join_campaign(e, id, token0_amt, account_address)
+    fn positions_of(e: Env, owner: Address) -> Vec<u64> {
+        let mut out = Vec::new(&e);
+        for id in get_user_camps(&e, &owner).iter() {
+            if let Some(up) = e.storage().instance().get::<Val, UserPos>(&upos_key(&e, id, &owner)) {
+                out.push_back(position::token_id(id, up.rank));
+            }
+        }
+        out
     }
 
     // ------------------------------------------------------ claim --
@@ -833,26 +1612,79 @@ impl Manager for FlashCampaignManager {
             e.ledger().sequence() >= c.end_ledger,
             FlashErr::TooEarly
         );
+        pay_out(&e, id, &c, &user, &claim_recipient(&e, &user), 0)?;
+        Ok(())
+        //log!(&e, "[CLAIM] id {:?} user {:?} flash {:?} lp {:?}", id, user, total, up.lp);
+    }
 
-        let key = upos_key(&e, id, &user);
-        let up: UserPos = e.storage().instance().get(&key).unwrap_optimized();
-        ensure!(&e, up.weight > 0, FlashErr::NothingToClaim);
+    fn claim_many(e: Env, ids: Vec<u32>, user: Address, recipient: Address) -> Vec<ClaimResult> {
+        bump(&e);
+        user.require_auth();
+        let mut out = Vec::new(&e);
+        for id in ids.iter() {
+            out.push_back(try_claim(&e, id, &user, &recipient, 0).0);
+        }
+        out
+    }
 
-        let base = c.reward_flash * up.weight / c.total_weight;
-        let bonus = if c.total_lp >= c.target_lp {
-            c.bonus_flash * up.weight / c.total_weight
-        } else {
-            0
-        };
-        let total = base + bonus;
-        let config = get_core_config(&e);
-        TokenClient::new(&e, &config.flash).transfer(&e.current_contract_address(), &user, &total);
+    fn claim_all(e: Env, user: Address) -> Vec<ClaimResult> {
+        bump(&e);
+        user.require_auth();
+        let mut out = Vec::new(&e);
+        let recipient = claim_recipient(&e, &user);
+        for id in get_user_camps(&e, &user).iter() {
+            out.push_back(try_claim(&e, id, &user, &recipient, 0).0);
+        }
+        out
+    }
 
-        TokenClient::new(&e, &c.pair).transfer(&e.current_contract_address(), &user, &up.lp);
+    fn set_claim_recipient(e: Env, user: Address, recipient: Option<Address>) {
+        bump(&e);
+        user.require_auth();
+        set_recipient(&e, &user, recipient);
+    }
 
-        e.storage().instance().remove(&key);
+    fn enable_distribution(e: Env, id: u32, bounty_bps: u32, creator: Address) -> Result<(), FlashErr> {
+        bump(&e);
+        creator.require_auth();
+        let mut c = load_camp(&e, id);
+        ensure!(&e, c.creator == creator, FlashErr::NotCreator);
+        ensure!(&e, bounty_bps <= MAX_KEEPER_BPS, FlashErr::BpsOutOfRange);
+        ensure!(&e, e.ledger().sequence() < c.end_ledger, FlashErr::CampaignEnded);
+        c.keeper_bps = Some(bounty_bps);
+        save_camp(&e, id, &c);
         Ok(())
-        //log!(&e, "[CLAIM] id {:?} user {:?} flash {:?} lp {:?}", id, user, total, up.lp);
+    }
+
+    fn distribute(
+        e: Env,
+        id: u32,
+        start: u32,
+        limit: u32,
+        keeper: Address,
+    ) -> Result<Vec<ClaimResult>, FlashErr> {
+        bump(&e);
+        keeper.require_auth();
+        let c = load_camp(&e, id);
+        let bps = c.keeper_bps.ok_or(FlashErr::DistributionOff)?;
+        ensure!(&e, e.ledger().sequence() >= c.end_ledger, FlashErr::TooEarly);
+
+        let joined = e.storage().instance().get::<Val, u32>(&ucnt_key(&e, id)).unwrap_or(0);
+        let end = start.saturating_add(limit).min(joined);
+        let mut out = Vec::new(&e);
+        let mut bounty = 0;
+        // ranks are 1-based, `start` is an offset into them
+        for rank in start.saturating_add(1)..=end {
+            let Some(user) = get_rank_user(&e, id, rank) else { continue };
+            let (r, cut) = try_claim(&e, id, &user, &claim_recipient(&e, &user), bps);
+            bounty += cut;
+            out.push_back(r);
+        }
+        if bounty > 0 {
+            let flash = get_core_config(&e).flash;
+            TokenClient::new(&e, &flash).transfer(&e.current_contract_address(), &keeper, &bounty);
+        }
+        Ok(out)
     }
 
     // -------------------------------------------------- compound ---
@@ -927,6 +1759,33 @@ impl Manager for FlashCampaignManager {
         Ok(())
         //log!(&e, "[ADMIN] ttl threshold {} bump {}", threshold, bump_);
     }
+
+    /// Accounts already deployed keep their code; only new ones use the new hash.
+    fn set_account_wasm(e: Env, admin: Address, wasm_hash: BytesN<32>) -> Result<(), FlashErr> {
+        admin.require_auth();
+        let mut config = get_core_config(&e);
+        ensure!(&e, admin == config.admin, FlashErr::NotAdmin);
+        config.account_wasm = wasm_hash;
+        set_core_config(&e, &config);
+        Ok(())
+    }
+
+    fn set_account_rp_id(e: Env, admin: Address, rp_id: Bytes) -> Result<(), FlashErr> {
+        admin.require_auth();
+        let mut config = get_core_config(&e);
+        ensure!(&e, admin == config.admin, FlashErr::NotAdmin);
+        config.account_rp_id = Some(rp_id);
+        set_core_config(&e, &config);
+        Ok(())
+    }
+
+    fn account_exists(e: Env, user: Address) -> bool {
+        get_account(&e, &user).is_some()
+    }
+
+    fn account_of(e: Env, user: Address) -> Option<Address> {
+        get_account(&e, &user)
+    }
 }
 
 // -------------------------------------------------------------
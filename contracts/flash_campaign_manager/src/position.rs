@@ -0,0 +1,51 @@
+//! Campaign positions as non-fungible tokens.
+//!
+//! A position's token id packs the campaign id with the join rank it was
+//! minted under, so ids are unique without a counter and survive transfers.
+//! Events use the SEP-41 topic layout with the token id as data.
+//! Owners can approve a spender for one position, or an operator for all of
+//! them, who then moves it with `transfer_position_from`.
+use soroban_sdk::{symbol_short, Address, Env, Val};
+
+use crate::storage::{get_rank_user, upos_key};
+use crate::UserPos;
+
+pub fn token_id(id: u32, rank: u32) -> u64 {
+    ((id as u64) << 32) | rank as u64
+}
+
+/// `(campaign id, rank)` of a token id.
+pub fn split(token_id: u64) -> (u32, u32) {
+    ((token_id >> 32) as u32, token_id as u32)
+}
+
+/// Current holder of `token_id`; `None` once claimed or if never minted.
+pub fn owner_of(e: &Env, token_id: u64) -> Option<Address> {
+    let (id, rank) = split(token_id);
+    let user = get_rank_user(e, id, rank)?;
+    let up = e.storage().instance().get::<Val, UserPos>(&upos_key(e, id, &user))?;
+    (up.rank == rank).then_some(user)
+}
+
+pub fn mint_event(e: &Env, to: &Address, token_id: u64) {
+    e.events().publish((symbol_short!("mint"), to.clone()), token_id);
+}
+
+pub fn transfer_event(e: &Env, from: &Address, to: &Address, token_id: u64) {
+    e.events()
+        .publish((symbol_short!("transfer"), from.clone(), to.clone()), token_id);
+}
+
+pub fn approve_event(e: &Env, owner: &Address, spender: &Option<Address>, token_id: u64) {
+    e.events()
+        .publish((symbol_short!("approve"), owner.clone()), (token_id, spender.clone()));
+}
+
+pub fn operator_event(e: &Env, owner: &Address, operator: &Address, approved: bool) {
+    e.events()
+        .publish((symbol_short!("operator"), owner.clone(), operator.clone()), approved);
+}
+
+pub fn burn_event(e: &Env, from: &Address, token_id: u64) {
+    e.events().publish((symbol_short!("burn"), from.clone()), token_id);
+}
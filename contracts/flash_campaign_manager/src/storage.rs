@@ -1,6 +1,6 @@
-use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, IntoVal, Symbol, Val, symbol_short};
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Bytes, BytesN, Env, IntoVal, Symbol, TryFromVal, Val, Vec, symbol_short};
 
-use crate::Campaign;
+use crate::{Campaign, Proposal};
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CoreConfig {
@@ -12,8 +12,11 @@ pub struct CoreConfig {
     pub surplus_bps: u32,
     pub ttl_thresh: u32,
     pub ttl_bump: u32,
+    pub account_wasm: BytesN<32>, // code for new per-user smart accounts
+    pub account_rp_id: Option<Bytes>, // passkey relying party id for new accounts
 }
 
+/// Entry of a pair's campaign index.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ActiveCampaignInfo {
@@ -24,10 +27,55 @@ pub struct ActiveCampaignInfo {
 pub const PREFIX_UCNT: &str = "UC";
 pub const PREFIX_CAMP: &str = "C";
 pub const PREFIX_UPOS: &str = "U";
+pub const PREFIX_ALW: &str = "AL";
+pub const PREFIX_PAIR: &str = "PC";
+pub const PREFIX_FUND: &str = "F";
+pub const PREFIX_PROP: &str = "P";
+pub const PREFIX_PLEDGE: &str = "PL";
+pub const PREFIX_UCAMP: &str = "UJ";
+pub const PREFIX_RANK: &str = "RK";
+pub const PREFIX_RCPT: &str = "CR";
+pub const PREFIX_ACCT: &str = "AC";
+pub const PREFIX_APPR: &str = "AP";
+pub const PREFIX_OPER: &str = "OP";
 
 pub fn camp_key(e:&Env,id:u32)->Val { (PREFIX_CAMP,id).into_val(e) }
 pub fn upos_key(e:&Env,id:u32,w:&Address)->Val { (PREFIX_UPOS,id,w).into_val(e) }
 pub fn ucnt_key(e: &Env, id: u32) -> Val { (PREFIX_UCNT, id).into_val(e) }
+pub fn alw_key(e: &Env, id: u32, w: &Address) -> Val { (PREFIX_ALW, id, w).into_val(e) }
+pub fn pair_key(e: &Env, pair: &Address) -> Val { (PREFIX_PAIR, pair).into_val(e) }
+pub fn fund_key(e: &Env, id: u32, w: &Address) -> Val { (PREFIX_FUND, id, w).into_val(e) }
+pub fn prop_key(e: &Env, id: u32) -> Val { (PREFIX_PROP, id).into_val(e) }
+pub fn rank_key(e: &Env, id: u32, rank: u32) -> Val { (PREFIX_RANK, id, rank).into_val(e) }
+pub fn rcpt_key(e: &Env, w: &Address) -> Val { (PREFIX_RCPT, w).into_val(e) }
+pub fn acct_key(e: &Env, w: &Address) -> Val { (PREFIX_ACCT, w).into_val(e) }
+pub fn ucamp_key(e: &Env, w: &Address) -> Val { (PREFIX_UCAMP, w).into_val(e) }
+pub fn pledge_key(e: &Env, id: u32, w: &Address) -> Val { (PREFIX_PLEDGE, id, w).into_val(e) }
+pub fn appr_key(e: &Env, token_id: u64) -> Val { (PREFIX_APPR, token_id).into_val(e) }
+pub fn oper_key(e: &Env, w: &Address, op: &Address) -> Val { (PREFIX_OPER, w, op).into_val(e) }
+
+// Per-user entries (allowance counters, funding, pledges, the user's
+// campaign index, rank holders, claim recipients, accounts) grow without
+// bound, so they live in persistent storage and are bumped on every touch
+// with the same TTL settings as the instance.
+fn p_bump(e: &Env, key: &Val) {
+    let config = get_core_config(e);
+    e.storage().persistent().extend_ttl(key, config.ttl_thresh, config.ttl_bump);
+}
+fn p_get<V: TryFromVal<Env, Val>>(e: &Env, key: &Val) -> Option<V> {
+    let v = e.storage().persistent().get::<Val, V>(key);
+    if v.is_some() {
+        p_bump(e, key);
+    }
+    v
+}
+fn p_set<V: IntoVal<Env, Val>>(e: &Env, key: &Val, v: &V) {
+    e.storage().persistent().set::<Val, V>(key, v);
+    p_bump(e, key);
+}
+fn p_remove(e: &Env, key: &Val) {
+    e.storage().persistent().remove(key);
+}
 
 pub fn load_camp(e:&Env,id:u32)->Campaign {
     e.storage().instance().get::<Val,Campaign>(&camp_key(e,id)).unwrap_optimized()
@@ -36,6 +84,53 @@ pub fn save_camp(e:&Env,id:u32,c:&Campaign){
     e.storage().instance().set::<Val,Campaign>(&camp_key(e,id),c)
 }
 
+/// token0 already deposited by `user` against their allowlist cap
+pub fn get_allow_used(e: &Env, id: u32, user: &Address) -> i128 {
+    p_get::<i128>(e, &alw_key(e, id, user)).unwrap_or(0)
+}
+pub fn set_allow_used(e: &Env, id: u32, user: &Address, used: i128) {
+    p_set(e, &alw_key(e, id, user), &used)
+}
+
+/// What one funder has put into a campaign's reward pool so far.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Funding {
+    pub usdc: i128,  // fee paid in
+    pub flash: i128, // reward FLASH it bought
+}
+
+pub fn get_funding(e: &Env, id: u32, funder: &Address) -> Funding {
+    p_get::<Funding>(e, &fund_key(e, id, funder)).unwrap_or_default()
+}
+pub fn add_funding(e: &Env, id: u32, funder: &Address, usdc: i128, flash: i128) {
+    let mut f = get_funding(e, id, funder);
+    f.usdc += usdc;
+    f.flash += flash;
+    p_set(e, &fund_key(e, id, funder), &f)
+}
+
+pub const KEY_PROP_NEXT: Symbol = symbol_short!("PNEXT");
+
+pub fn next_prop_id(e: &Env) -> u32 {
+    let id = e.storage().instance().get::<Symbol, u32>(&KEY_PROP_NEXT).unwrap_or(0) + 1;
+    e.storage().instance().set(&KEY_PROP_NEXT, &id);
+    id
+}
+pub fn load_prop(e: &Env, id: u32) -> Option<Proposal> {
+    e.storage().instance().get::<Val, Proposal>(&prop_key(e, id))
+}
+pub fn save_prop(e: &Env, id: u32, p: &Proposal) {
+    e.storage().instance().set::<Val, Proposal>(&prop_key(e, id), p)
+}
+
+/// USDC `sponsor` has pledged to proposal `id`; kept after activation for attribution.
+pub fn get_pledge(e: &Env, id: u32, sponsor: &Address) -> i128 {
+    p_get::<i128>(e, &pledge_key(e, id, sponsor)).unwrap_or(0)
+}
+pub fn set_pledge(e: &Env, id: u32, sponsor: &Address, amount: i128) {
+    p_set(e, &pledge_key(e, id, sponsor), &amount)
+}
 
 pub fn set_core_config(e: &Env, config: &CoreConfig) {
     e.storage().instance().set(&KEY_CORE_CONFIG, config);
@@ -50,17 +145,118 @@ pub fn update_core_config<F: FnOnce(&mut CoreConfig)>(e: &Env, f: F) {
     set_core_config(e, &config);
 }
 
-pub fn set_active_campaign_for_pair(e: &Env, pair: &Address, info: &ActiveCampaignInfo) {
-    e.storage().instance().set(pair, info);
+/// Campaigns indexed under `pair`; entries may have ended since they were written.
+pub fn get_pair_campaigns(e: &Env, pair: &Address) -> Vec<ActiveCampaignInfo> {
+    e.storage()
+        .instance()
+        .get::<Val, Vec<ActiveCampaignInfo>>(&pair_key(e, pair))
+        .unwrap_or(Vec::new(e))
 }
 
-pub fn get_active_campaign_for_pair(e: &Env, pair: &Address) -> Option<ActiveCampaignInfo> {
-    e.storage().instance().get(pair)
+/// Adds a campaign to the pair's index and drops the ones that have ended.
+pub fn add_pair_campaign(e: &Env, pair: &Address, info: &ActiveCampaignInfo) {
+    let now = e.ledger().sequence();
+    let mut live = Vec::new(e);
+    for i in get_pair_campaigns(e, pair).iter() {
+        if i.end_ledger > now {
+            live.push_back(i);
+        }
+    }
+    live.push_back(info.clone());
+    e.storage()
+        .instance()
+        .set::<Val, Vec<ActiveCampaignInfo>>(&pair_key(e, pair), &live);
 }
 
-#[allow(dead_code)]
-pub fn clear_active_campaign_for_pair(e: &Env, pair: &Address) {
-    e.storage().instance().remove(pair);
+pub const KEY_CORE_CONFIG: Symbol = symbol_short!("CONFIG");
+
+/// Moves a campaign's end in the pair index after an extension.
+pub fn set_pair_campaign_end(e: &Env, pair: &Address, id: u32, end_ledger: u32) {
+    let mut list = get_pair_campaigns(e, pair);
+    for i in 0..list.len() {
+        let mut info = list.get_unchecked(i);
+        if info.campaign_id == id {
+            info.end_ledger = end_ledger;
+            list.set(i, info);
+        }
+    }
+    e.storage()
+        .instance()
+        .set::<Val, Vec<ActiveCampaignInfo>>(&pair_key(e, pair), &list);
+}
+
+/// Campaigns `user` holds an unclaimed position in, in join order.
+pub fn get_user_camps(e: &Env, user: &Address) -> Vec<u32> {
+    p_get::<Vec<u32>>(e, &ucamp_key(e, user)).unwrap_or(Vec::new(e))
+}
+pub fn add_user_camp(e: &Env, user: &Address, id: u32) {
+    let mut ids = get_user_camps(e, user);
+    ids.push_back(id);
+    p_set(e, &ucamp_key(e, user), &ids);
+}
+pub fn remove_user_camp(e: &Env, user: &Address, id: u32) {
+    let mut ids = get_user_camps(e, user);
+    if let Some(i) = ids.first_index_of(id) {
+        ids.remove(i);
+    }
+    if ids.is_empty() {
+        p_remove(e, &ucamp_key(e, user));
+    } else {
+        p_set(e, &ucamp_key(e, user), &ids);
+    }
 }
 
-pub const KEY_CORE_CONFIG: Symbol = symbol_short!("CONFIG");
\ No newline at end of file
+/// Holder of the position minted under `rank` in campaign `id`; lets
+/// `distribute` page in join order and follows position transfers.
+pub fn get_rank_user(e: &Env, id: u32, rank: u32) -> Option<Address> {
+    p_get::<Address>(e, &rank_key(e, id, rank))
+}
+pub fn set_rank_user(e: &Env, id: u32, rank: u32, user: &Address) {
+    p_set(e, &rank_key(e, id, rank), user)
+}
+pub fn remove_rank_user(e: &Env, id: u32, rank: u32) {
+    p_remove(e, &rank_key(e, id, rank))
+}
+
+/// Spender `owner` approved for `token_id`. The approval is kept with the
+/// owner who gave it, so it lapses once the position changes hands.
+pub fn get_position_approval(e: &Env, token_id: u64, owner: &Address) -> Option<Address> {
+    let (by, spender) = p_get::<(Address, Address)>(e, &appr_key(e, token_id))?;
+    (by == *owner).then_some(spender)
+}
+pub fn set_position_approval(e: &Env, token_id: u64, owner: &Address, spender: Option<Address>) {
+    match spender {
+        Some(s) => p_set(e, &appr_key(e, token_id), &(owner.clone(), s)),
+        None => p_remove(e, &appr_key(e, token_id)),
+    }
+}
+
+/// Whether `operator` may move every position `owner` holds.
+pub fn is_operator(e: &Env, owner: &Address, operator: &Address) -> bool {
+    p_get::<bool>(e, &oper_key(e, owner, operator)).unwrap_or(false)
+}
+pub fn set_operator(e: &Env, owner: &Address, operator: &Address, approved: bool) {
+    if approved {
+        p_set(e, &oper_key(e, owner, operator), &true)
+    } else {
+        p_remove(e, &oper_key(e, owner, operator))
+    }
+}
+
+pub fn claim_recipient(e: &Env, user: &Address) -> Address {
+    p_get::<Address>(e, &rcpt_key(e, user)).unwrap_or(user.clone())
+}
+pub fn set_recipient(e: &Env, user: &Address, recipient: Option<Address>) {
+    match recipient {
+        Some(r) if r != *user => p_set(e, &rcpt_key(e, user), &r),
+        _ => p_remove(e, &rcpt_key(e, user)),
+    }
+}
+
+/// Smart account this contract deployed for `user`.
+pub fn get_account(e: &Env, user: &Address) -> Option<Address> {
+    p_get::<Address>(e, &acct_key(e, user))
+}
+pub fn set_account(e: &Env, user: &Address, account: &Address) {
+    p_set(e, &acct_key(e, user), account)
+}
@@ -9,6 +9,7 @@ use soroban_sdk::{ bytes, token, Address, Env, String, Vec };
 extern crate std;
 
 pub mod campaign_manager {
+    use smart_wallet_interface::types::Signer;
     soroban_sdk::contractimport!(
         file = "../target/wasm32v1-none/release/flash_campaign_manager.wasm"
     );
@@ -26,11 +27,13 @@ pub mod soroswap_factory {
 }
 pub mod account_contract {
     use soroban_sdk::auth::Context;
-    use smart_wallet_interface::types::Signatures;
+    use smart_wallet_interface::types::{Signatures, Signer, SignerKey};
+    use smart_wallet_interface::upgrade::PendingRemoval;
     soroban_sdk::contractimport!(file = "../target/wasm32v1-none/release/account.wasm");
     pub type AccountClient<'a> = Client<'a>;
 }
 use account_contract::AccountClient;
+use smart_wallet_interface::types::{SignerExpiration, SignerLimits, SignerStorage};
 // use soroswap_factory::SoroswapFactoryClient;
 
 // For tests: Use 7 decimals for tokens!
@@ -44,7 +47,17 @@ fn join_campaign_for_test<'a>(
     amount: i128,
     user: &Address,
 ) {
-    mgr.join_campaign(cid, &amount, user);
+    mgr.join_campaign(cid, &amount, user, &None);
+}
+
+// Helper: an unlimited, persistent ed25519 signer for a user's smart account
+fn test_signer(e: &Env, seed: u8) -> Signer {
+    Signer::Ed25519(
+        BytesN::from_array(e, &[seed; 32]),
+        SignerExpiration(None),
+        SignerLimits(None),
+        SignerStorage::Persistent,
+    )
 }
 
 // Helper: creates a campaign and returns the campaign id
@@ -66,6 +79,12 @@ fn create_campaign_for_test<'a>(
         &unlock,
         &target_lp,
         &bonus_flash,
+        &None,
+        &campaign_manager::CampaignLimits {
+            min_deposit: 0,
+            max_deposit_per_user: None,
+            hard_cap_lp: None,
+        },
         creator,
     );
     // 2) panic if the host invocation itself failed
@@ -409,6 +428,8 @@ fn test_create_campaigns_native() {
             10,
             100_000 * TOKEN_UNIT,
             0,
+            None,
+            CampaignLimits::default(),
             alice.clone(),
         )
     });
@@ -416,7 +437,7 @@ fn test_create_campaigns_native() {
         Ok(id) => std::println!("[TEST_NATIVE] Campaign 1 created with cid={}", id),
         Err(e) => std::println!("[TEST_NATIVE] Campaign 1 creation failed: {:?}", e),
     }
-    // Try to create a second campaign for the same pair (should fail)
+    // A second sponsor can run a campaign on the same pair at the same time
     let cid2 = e.as_contract(&mgr, || {
         crate::FlashCampaignManager::create_campaign(
             e.clone(),
@@ -425,14 +446,16 @@ fn test_create_campaigns_native() {
             10,
             100_000 * TOKEN_UNIT,
             0,
+            None,
+            CampaignLimits::default(),
             bob.clone(),
         )
     });
-    assert_eq!(
-        cid2,
-        Err(FlashErr::CampaignActiveForPair),
-        "Second campaign creation for the same pair should fail"
-    );
+    assert!(cid2.is_ok(), "Second campaign for the same pair should succeed");
+    let live = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::pair_campaigns(e.clone(), usdc_eurc_pair.clone())
+    });
+    assert_eq!(live.len(), 2);
 
     // Create campaign for a different pair (usdc_some_pair) - should succeed
     let cid3 = e.as_contract(&mgr, || {
@@ -443,6 +466,8 @@ fn test_create_campaigns_native() {
             10,
             100_000 * TOKEN_UNIT,
             0,
+            None,
+            CampaignLimits::default(),
             bob.clone(),
         )
     });
@@ -455,11 +480,10 @@ fn test_create_campaigns_native() {
 // Test: create campaign with single user, no bonus
 #[test]
 fn test_create_and_join_campaign() {
-    use soroban_sdk::BytesN;
     let (
         e,
         mgr,
-        _god,
+        god,
         users,
         _flash,
         _usdc,
@@ -478,20 +502,35 @@ fn test_create_and_join_campaign() {
     let campaign_id = cid1.unwrap();
     std::println!("[TEST_NATIVE] Campaign 1 created with cid={}", campaign_id);
 
-    let account_wasm = account_contract::WASM;
-    let account_wasm_bytes = Bytes::from_slice(&e, account_wasm);
-    let account_wasm_hash: BytesN<32> = e.crypto().sha256(&account_wasm_bytes).into(); 
-
     // Join campaign as single user via smart account
     let join_amount = 2000 * TOKEN_UNIT;
-    let join_result = mgr.try_join_campaign_with_account(&campaign_id, &join_amount, &alice);
+    assert!(!mgr.account_exists(&alice));
+    // the first join must seed the account's signer
+    assert!(mgr
+        .try_join_campaign_with_account(&campaign_id, &join_amount, &alice, &None, &None)
+        .is_err());
+    // and passkeys need the app's relying party first
+    assert_eq!(
+        mgr.try_join_campaign_with_account(&campaign_id, &join_amount, &alice, &None, &Some(test_signer(&e, 1))),
+        Err(Ok(campaign_manager::FlashErr::AccountRpIdNotSet))
+    );
+    mgr.set_account_rp_id(&god, &Bytes::from_slice(&e, b"flashpool.app"));
+    let join_result = mgr.try_join_campaign_with_account(
+        &campaign_id,
+        &join_amount,
+        &alice,
+        &None,
+        &Some(test_signer(&e, 1)),
+    );
     assert!(join_result.is_ok(), "User should be able to join the campaign via smart account");
-
-    // Check user position exists for smart account
-    //let salt = alice.clone().into_val(&e);
-    let user_bytes = &alice.clone().to_xdr(&e);
-    let user_salt: BytesN<32> = BytesN::from_xdr(&e, &user_bytes).unwrap();
-    let account_address = e.deployer().with_current_contract(user_salt).deployed_address();
+    let account_address = mgr.account_of(&alice).unwrap();
+    assert!(mgr.account_exists(&alice));
+    assert_eq!(AccountClient::new(&e, &account_address).owner(), alice);
+
+    // a second join reuses the deployed account
+    let join_result = mgr.try_join_campaign_with_account(&campaign_id, &join_amount, &alice, &None, &None);
+    assert!(join_result.is_ok(), "Second join should reuse the smart account");
+    assert_eq!(mgr.account_of(&alice), Some(account_address.clone()));
     std::println!(
         "[TEST_NATIVE] User smart account address: {:?}",
         account_address
@@ -509,7 +548,7 @@ fn test_create_and_join_campaign_native() {
     let (
         e,
         mgr,
-        _god,
+        god,
         users,
         _flash,
         _usdc,
@@ -531,68 +570,942 @@ fn test_create_and_join_campaign_native() {
             10,
             100_000 * TOKEN_UNIT,
             0,
+            None,
+            CampaignLimits::default(),
             alice.clone(),
         )
     }).expect("Campaign creation should succeed");
     std::println!("[TEST_NATIVE] Campaign created with cid={}", campaign_id);
 
-    // Prepare smart account salt and address
-    let user_bytes = &alice.clone().to_xdr(&e);
-    std::println!("[join_native_test] User bytes: {:?}", user_bytes);
-    //let user_salt: BytesN<32> = BytesN::from_xdr(&e, &user_bytes).unwrap();
-    let user_salt: BytesN<32> = e.crypto().sha256(&user_bytes).into();
-    std::println!("[join_native_test] User salt: {:?}", user_salt);
-    let account_address = e.as_contract(&mgr, || {e.deployer().with_current_contract(user_salt.clone()).deployed_address()});
-    std::println!(
-        "[TEST_NATIVE] User smart account address: {:?}",
-        account_address
+    // Join campaign as single user via smart account, natively (so logs are visible)
+    let join_amount = 2000 * TOKEN_UNIT;
+    let join = |signer: Option<Signer>| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign_with_account(
+                e.clone(),
+                campaign_id,
+                join_amount,
+                alice.clone(),
+                None,
+                signer,
+            )
+        })
+    };
+    assert_eq!(join(None), Err(FlashErr::AccountSignerRequired));
+    e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::set_account_rp_id(e.clone(), god.clone(), Bytes::from_slice(&e, b"flashpool.app"))
+    })
+    .unwrap();
+    let join_result = join(Some(test_signer(&e, 1)));
+    assert!(join_result.is_ok(), "User should be able to join the campaign via smart account");
+    std::println!("[TEST_NATIVE] User joined campaign via smart account: {:?}", join_result);
+    assert!(join(None).is_ok(), "Second join should reuse the smart account");
+
+    // The account sits at the manager-salted address and holds one position
+    let user_salt: BytesN<32> = e.crypto().sha256(&alice.clone().to_xdr(&e)).into();
+    let account_address = e.as_contract(&mgr, || {
+        e.deployer().with_current_contract(user_salt.clone()).deployed_address()
+    });
+    let exists = e.as_contract(&mgr, || crate::FlashCampaignManager::account_exists(e.clone(), alice.clone()));
+    assert!(exists);
+    let up: UserPos = e.as_contract(&mgr, || {
+        e.storage().instance().get(&upos_key(&e, campaign_id, &account_address)).unwrap()
+    });
+    assert_eq!(up.deposit, 2 * join_amount);
+
+    // Optionally: Check user position exists for smart account
+    // ...
+}
+#[test]
+fn test_allowlist_gated_join_native() {
+    use flash_allowlist::{AllowlistTree, Entry};
+    use soroban_sdk::BytesN;
+    std::println!("[TEST] ---- test_allowlist_gated_join_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_allowlist_gated_join_native");
+    let alice = users.get(0).unwrap();
+    let bob = users.get(1).unwrap();
+    let carol = users.get(2).unwrap();
+
+    // alice and carol are on the snapshot, bob is not
+    let tree = AllowlistTree::new(std::vec![
+        Entry { user: (&alice).into(), max_amount: 1_000 * TOKEN_UNIT },
+        Entry { user: (&carol).into(), max_amount: 500 * TOKEN_UNIT },
+    ]);
+    let root = BytesN::from_array(&e, &tree.root());
+    let proof_for = |who: &Address| {
+        let (max_amount, path) = tree.proof_for(&who.into()).unwrap();
+        let mut proof = Vec::new(&e);
+        for h in path {
+            proof.push_back(BytesN::from_array(&e, &h));
+        }
+        AllowProof { max_amount, proof }
+    };
+
+    let cid = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::create_campaign(
+            e.clone(),
+            500 * TOKEN_UNIT,
+            usdc_eurc_pair.clone(),
+            10,
+            100_000 * TOKEN_UNIT,
+            0,
+            Some(root.clone()),
+            CampaignLimits::default(),
+            alice.clone(),
+        )
+    }).expect("Campaign creation should succeed");
+
+    let join = |who: &Address, amt: i128, proof: Option<AllowProof>| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), cid, amt, who.clone(), proof)
+        })
+    };
+
+    // no proof, and a proof borrowed from someone else
+    assert_eq!(join(&bob, 100 * TOKEN_UNIT, None), Err(FlashErr::NotAllowlisted));
+    assert_eq!(
+        join(&bob, 100 * TOKEN_UNIT, Some(proof_for(&alice))),
+        Err(FlashErr::NotAllowlisted)
+    );
+    // claiming a larger cap than the leaf commits to
+    let mut inflated = proof_for(&carol);
+    inflated.max_amount = 5_000 * TOKEN_UNIT;
+    assert_eq!(join(&carol, 600 * TOKEN_UNIT, Some(inflated)), Err(FlashErr::NotAllowlisted));
+
+    // the cap holds across repeat deposits
+    assert_eq!(join(&alice, 600 * TOKEN_UNIT, Some(proof_for(&alice))), Ok(()));
+    assert_eq!(
+        join(&alice, 600 * TOKEN_UNIT, Some(proof_for(&alice))),
+        Err(FlashErr::AllowlistCapExceeded)
     );
+    assert_eq!(join(&alice, 400 * TOKEN_UNIT, Some(proof_for(&alice))), Ok(()));
+
+    let up: UserPos = e.as_contract(&mgr, || {
+        e.storage().instance().get(&upos_key(&e, cid, &alice)).unwrap()
+    });
+    std::println!("[TEST_NATIVE] alice position after two joins: {:?}", up);
+    assert_eq!(up.rank, 1);
+}
+#[test]
+fn test_join_limits_native() {
+    std::println!("[TEST] ---- test_join_limits_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_join_limits_native");
+    let alice = users.get(0).unwrap();
+    let bob = users.get(1).unwrap();
 
-    // Deploy the account contract if not already deployed
-    let account_wasm = account_contract::WASM;
-    let account_wasm_bytes = soroban_sdk::Bytes::from_slice(&e, account_wasm);
-    let account_wasm_hash: BytesN<32> = e.crypto().sha256(&account_wasm_bytes).into();
+    let create = |limits: CampaignLimits| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::create_campaign(
+                e.clone(),
+                500 * TOKEN_UNIT,
+                usdc_eurc_pair.clone(),
+                10,
+                500 * TOKEN_UNIT,
+                0,
+                None,
+                limits,
+                alice.clone(),
+            )
+        })
+    };
 
-    // we need to first upload the wasm to the ledger. using the deployer.
+    // invalid limits are refused up front
+    let bad = [
+        CampaignLimits { min_deposit: -1, ..Default::default() },
+        CampaignLimits {
+            min_deposit: 100 * TOKEN_UNIT,
+            max_deposit_per_user: Some(50 * TOKEN_UNIT),
+            hard_cap_lp: None,
+        },
+        CampaignLimits { hard_cap_lp: Some(10 * TOKEN_UNIT), ..Default::default() },
+    ];
+    for l in bad {
+        assert_eq!(create(l), Err(FlashErr::InvalidLimits));
+    }
 
-    let uploaded_wasm_hash: BytesN<32> = e.as_contract(&mgr, || {
-    e.deployer().upload_contract_wasm(account_wasm_bytes.clone())
-});
-    
-    std::println!(
-        "[TEST_NATIVE] Uploaded account contract wasm hash: {:?}",
-        uploaded_wasm_hash
+    let cid = create(CampaignLimits {
+        min_deposit: 10 * TOKEN_UNIT,
+        max_deposit_per_user: Some(1_000 * TOKEN_UNIT),
+        hard_cap_lp: Some(600 * TOKEN_UNIT),
+    })
+    .expect("Campaign creation should succeed");
+
+    let join = |who: &Address, amt: i128| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), cid, amt, who.clone(), None)
+        })
+    };
+
+    assert_eq!(join(&alice, 5 * TOKEN_UNIT), Err(FlashErr::BelowMinDeposit));
+    assert_eq!(join(&alice, 800 * TOKEN_UNIT), Ok(()));
+    // per-user cap is summed over repeat joins
+    assert_eq!(join(&alice, 300 * TOKEN_UNIT), Err(FlashErr::UserCapExceeded));
+    assert_eq!(join(&alice, 200 * TOKEN_UNIT), Ok(()));
+
+    let c: Campaign = e.as_contract(&mgr, || load_camp(&e, cid));
+    std::println!("[TEST_NATIVE] total_lp after alice: {:.7}", c.total_lp as f64 * 1e-7);
+    // bob's deposit would push total_lp past the hard cap
+    assert_eq!(join(&bob, 1_000 * TOKEN_UNIT), Err(FlashErr::HardCapReached));
+}
+#[test]
+fn test_join_swap_amounts_native() {
+    std::println!("[TEST] ---- test_join_swap_amounts_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_join_swap_amounts_native");
+    let alice = users.get(0).unwrap();
+    let bob = users.get(1).unwrap();
+    let pcli = crate::pair::Client::new(&e, &usdc_eurc_pair);
+
+    let cid = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::create_campaign(
+            e.clone(),
+            500 * TOKEN_UNIT,
+            usdc_eurc_pair.clone(),
+            10,
+            100_000 * TOKEN_UNIT,
+            0,
+            None,
+            CampaignLimits::default(),
+            alice.clone(),
+        )
+    })
+    .unwrap();
+    let join = |amt: i128| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), cid, amt, alice.clone(), None)
+        })
+    };
+    let pos = || -> UserPos {
+        e.as_contract(&mgr, || e.storage().instance().get(&upos_key(&e, cid, &alice)).unwrap())
+    };
+
+    // half of the deposit is swapped at the fee-inclusive quote
+    let (r0, r1) = pcli.get_reserves();
+    join(1_000 * TOKEN_UNIT).unwrap();
+    let (a0, a1) = pcli.get_reserves();
+    assert_eq!((a0, a1), (r0 + 1_000 * TOKEN_UNIT, r1));
+    let first = pos();
+    std::println!("[TEST_NATIVE] first join: {:?}", first);
+    assert_eq!((first.lp, first.weight, first.rank), (5_480_558_768, 540_000, 1));
+
+    // a repeat join adds to the position and keeps its rank
+    join(500 * TOKEN_UNIT).unwrap();
+    let both = pos();
+    let c: Campaign = e.as_contract(&mgr, || load_camp(&e, cid));
+    std::println!("[TEST_NATIVE] after repeat join: {:?}", both);
+    assert_eq!((both.lp, both.weight, both.rank), (8_194_740_253, 810_000, 1));
+    assert_eq!(both.deposit, 1_500 * TOKEN_UNIT);
+    assert_eq!((c.total_lp, c.total_weight), (both.lp, both.weight));
+
+    // the fee-less quote r1 * half / (r0 + half) does not clear the pair's K check
+    let t0 = token::Client::new(&e, &pcli.token_0());
+    let half = 100 * TOKEN_UNIT;
+    let (r0, r1) = pcli.get_reserves();
+    t0.transfer(&bob, &usdc_eurc_pair, &half);
+    assert!(pcli.try_swap(&0, &(r1 * half / (r0 + half)), &bob).is_err());
+    assert!(pcli.try_swap(&0, &c_s_o(half, r0, r1), &bob).is_ok());
+}
+
+#[test]
+fn test_shared_deposit_across_pair_campaigns_native() {
+    use soroban_sdk::testutils::Ledger;
+    std::println!("[TEST] ---- test_shared_deposit_across_pair_campaigns_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_shared_deposit_across_pair_campaigns_native");
+    let sponsor = users.get(0).unwrap();
+    let dao = users.get(1).unwrap();
+    let alice = users.get(2).unwrap();
+
+    let create = |unlock: u32, creator: &Address| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::create_campaign(
+                e.clone(),
+                500 * TOKEN_UNIT,
+                usdc_eurc_pair.clone(),
+                unlock,
+                100_000 * TOKEN_UNIT,
+                0,
+                None,
+                CampaignLimits::default(),
+                creator.clone(),
+            )
+        })
+        .unwrap()
+    };
+    let short = create(10, &sponsor);
+    let long = create(20, &dao);
+
+    let ids = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::join_all_on_pair(
+            e.clone(),
+            short,
+            1_000 * TOKEN_UNIT,
+            alice.clone(),
+            None,
+        )
+    })
+    .unwrap();
+    assert_eq!(ids, Vec::from_array(&e, [short, long]));
+
+    let pos = |cid: u32| -> UserPos {
+        e.as_contract(&mgr, || e.storage().instance().get(&upos_key(&e, cid, &alice)).unwrap())
+    };
+    let (ps, pl) = (pos(short), pos(long));
+    assert_eq!(ps.lp, pl.lp);
+    // the full LP counts in both, and each escrows half of it
+    assert_eq!(ps.lp_held, ps.lp / 2);
+    assert_eq!(ps.lp_held + pl.lp_held, pl.lp);
+
+    let lp_token = token::Client::new(&e, &usdc_eurc_pair);
+    let claim = |cid: u32| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::claim(e.clone(), cid, alice.clone())
+        })
+    };
+    assert_eq!(claim(short), Err(FlashErr::TooEarly));
+
+    // the earlier campaign pays its reward and its slice without waiting on
+    // the later one, whose position stays intact
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let flash_before = flash.balance(&alice);
+    claim(short).unwrap();
+    assert!(flash.balance(&alice) > flash_before);
+    assert_eq!(lp_token.balance(&alice), ps.lp_held);
+    assert_eq!(claim(short), Err(FlashErr::NothingToClaim));
+    assert_eq!((pos(long).lp, pos(long).lp_held), (pl.lp, pl.lp_held));
+    assert_eq!(claim(long), Err(FlashErr::TooEarly));
+
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let flash_before = flash.balance(&alice);
+    claim(long).unwrap();
+    assert!(flash.balance(&alice) > flash_before);
+    assert_eq!(lp_token.balance(&alice), pl.lp);
+}
+
+#[test]
+fn test_top_up_campaign_native() {
+    use soroban_sdk::testutils::Ledger;
+    std::println!("[TEST] ---- test_top_up_campaign_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_top_up_campaign_native");
+    let sponsor = users.get(0).unwrap();
+    let dao = users.get(1).unwrap();
+
+    let create = |unlock: u32| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::create_campaign(
+                e.clone(),
+                500 * TOKEN_UNIT,
+                usdc_eurc_pair.clone(),
+                unlock,
+                100_000 * TOKEN_UNIT,
+                0,
+                None,
+                CampaignLimits::default(),
+                sponsor.clone(),
+            )
+        })
+    };
+    // no campaign starts past the length top-ups are capped at
+    assert_eq!(create(MAX_DURATION + 1), Err(FlashErr::DurationTooLong));
+    assert_eq!(create(u32::MAX), Err(FlashErr::DurationTooLong));
+
+    let id = create(10).unwrap();
+    let camp = || -> Campaign { e.as_contract(&mgr, || load_camp(&e, id)) };
+    let before = camp();
+
+    let top_up = |fee: i128, extra: u32, who: &Address| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::top_up_campaign(e.clone(), id, fee, extra, who.clone())
+        })
+    };
+
+    // a funder who is not the creator can add rewards but not extend, not
+    // even with a free top-up
+    assert_eq!(top_up(0, 5, &dao), Err(FlashErr::NotCreator));
+    assert_eq!(top_up(250 * TOKEN_UNIT, 5, &dao), Err(FlashErr::NotCreator));
+    let dao_usdc = usdc.balance(&dao);
+    let added = top_up(250 * TOKEN_UNIT, 0, &dao).unwrap();
+    assert!(added > 0);
+    assert_eq!(usdc.balance(&dao), dao_usdc - 250 * TOKEN_UNIT);
+    assert_eq!(top_up(0, 5, &sponsor), Ok(0));
+    let after = camp();
+    assert_eq!(after.reward_flash, before.reward_flash + added);
+    assert_eq!(after.end_ledger, before.end_ledger + 5);
+    assert_eq!(after.duration, 15);
+
+    let funding = |who: &Address| e.as_contract(&mgr, || crate::FlashCampaignManager::funding(e.clone(), id, who.clone()));
+    assert_eq!(funding(&sponsor).usdc, 500 * TOKEN_UNIT);
+    assert_eq!(funding(&sponsor).flash, before.reward_flash);
+    assert_eq!(funding(&dao), Funding { usdc: 250 * TOKEN_UNIT, flash: added });
+    // per-funder entries are persistent and carry the configured TTL
+    let ttl = e.as_contract(&mgr, || {
+        use soroban_sdk::testutils::storage::Persistent;
+        e.storage().persistent().get_ttl(&crate::storage::fund_key(&e, id, &dao))
+    });
+    assert_eq!(ttl, DEFAULT_TTL_BUMP);
+
+    // extension only, past the maximum length
+    assert_eq!(top_up(0, MAX_DURATION, &sponsor), Err(FlashErr::DurationTooLong));
+
+    // still listed for the pair past its original end
+    e.ledger().with_mut(|li| li.sequence_number += 12);
+    let ids = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::pair_campaigns(e.clone(), usdc_eurc_pair.clone())
+    });
+    assert_eq!(ids, Vec::from_array(&e, [id]));
+
+    e.ledger().with_mut(|li| li.sequence_number += 3);
+    assert_eq!(top_up(TOKEN_UNIT, 0, &dao), Err(FlashErr::CampaignEnded));
+}
+
+#[test]
+fn test_pledged_campaign_native() {
+    use soroban_sdk::testutils::Ledger;
+    std::println!("[TEST] ---- test_pledged_campaign_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_pledged_campaign_native");
+    let (a, b, c) = (users.get(0).unwrap(), users.get(1).unwrap(), users.get(2).unwrap());
+    let params = CampaignParams {
+        pair: usdc_eurc_pair.clone(),
+        unlock: 10,
+        target_lp: 100_000 * TOKEN_UNIT,
+        bonus_flash: 0,
+        allow_root: None,
+        limits: CampaignLimits::default(),
+    };
+    let propose = || {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::propose_campaign(e.clone(), params.clone(), 600 * TOKEN_UNIT, 20, a.clone())
+        })
+        .unwrap()
+    };
+    let pledge = |pid: u32, amt: i128, who: &Address| {
+        e.as_contract(&mgr, || crate::FlashCampaignManager::pledge(e.clone(), pid, amt, who.clone()))
+    };
+    let withdraw = |pid: u32, who: &Address| {
+        e.as_contract(&mgr, || crate::FlashCampaignManager::withdraw_pledge(e.clone(), pid, who.clone()))
+    };
+
+    // threshold met: the pot becomes one campaign, shares recorded per sponsor
+    let pid = propose();
+    assert_eq!(pledge(pid, 200 * TOKEN_UNIT, &a), Ok(None));
+    assert_eq!(pledge(pid, 100 * TOKEN_UNIT, &b), Ok(None));
+    assert_eq!(withdraw(pid, &a), Err(FlashErr::PledgeLocked));
+    let id = pledge(pid, 300 * TOKEN_UNIT, &b).unwrap().unwrap();
+    assert_eq!(pledge(pid, TOKEN_UNIT, &c), Err(FlashErr::PledgeClosed));
+
+    let camp: Campaign = e.as_contract(&mgr, || load_camp(&e, id));
+    assert_eq!(camp.pair, usdc_eurc_pair);
+    let funding = |who: &Address| e.as_contract(&mgr, || crate::FlashCampaignManager::funding(e.clone(), id, who.clone()));
+    let (fa, fb) = (funding(&a), funding(&b));
+    assert_eq!((fa.usdc, fb.usdc), (200 * TOKEN_UNIT, 400 * TOKEN_UNIT));
+    assert_eq!(fa.flash + fb.flash, camp.reward_flash);
+    assert!(fb.flash > fa.flash);
+
+    // threshold missed: pledges come back after the deadline
+    let pid = propose();
+    let c_usdc = usdc.balance(&c);
+    pledge(pid, 250 * TOKEN_UNIT, &c).unwrap();
+    e.ledger().with_mut(|li| li.sequence_number += 21);
+    assert_eq!(pledge(pid, 350 * TOKEN_UNIT, &a), Err(FlashErr::PledgeClosed));
+    assert_eq!(withdraw(pid, &c), Ok(250 * TOKEN_UNIT));
+    assert_eq!(usdc.balance(&c), c_usdc);
+    assert_eq!(withdraw(pid, &c), Err(FlashErr::NothingToClaim));
+
+    // the pledge window is bounded
+    for window in [0, MAX_PLEDGE_WINDOW + 1] {
+        let res = e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::propose_campaign(e.clone(), params.clone(), 600 * TOKEN_UNIT, window, a.clone())
+        });
+        assert_eq!(res, Err(FlashErr::InvalidPledgeWindow));
+    }
+    // and so is the campaign it would open
+    let too_long = CampaignParams { unlock: MAX_DURATION + 1, ..params.clone() };
+    let res = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::propose_campaign(e.clone(), too_long, 600 * TOKEN_UNIT, 20, a.clone())
+    });
+    assert_eq!(res, Err(FlashErr::DurationTooLong));
+
+    // a pot too small to buy any FLASH fails the fee split: the activating
+    // pledge reverts as a whole and the earlier one is refunded after the deadline
+    let client = FlashCampaignManagerClient::new(&e, &mgr);
+    let pid = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::propose_campaign(e.clone(), params.clone(), 2, 20, a.clone())
+    })
+    .unwrap();
+    let b_usdc = usdc.balance(&b);
+    assert_eq!(pledge(pid, 1, &a), Ok(None));
+    assert_eq!(
+        client.try_pledge(&pid, &1, &b),
+        Err(Ok(campaign_manager::FlashErr::Math))
+    );
+    assert_eq!(usdc.balance(&b), b_usdc);
+    assert_eq!(client.pledge_of(&pid, &b), 0);
+    let p = client.proposal(&pid).unwrap();
+    assert_eq!((p.pledged, p.campaign_id), (1, None));
+    e.ledger().with_mut(|li| li.sequence_number += 21);
+    assert_eq!(withdraw(pid, &a), Ok(1));
+}
+
+#[test]
+fn test_claim_all_and_claim_many_native() {
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    std::println!("[TEST] ---- test_claim_all_and_claim_many_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        usdc_some_pair,
+    ) = fresh_env_native("test_claim_all_and_claim_many_native");
+    let sponsor = users.get(0).unwrap();
+    let alice = users.get(1).unwrap();
+    let cold = Address::generate(&e);
+
+    let create = |pair: &Address, unlock: u32| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::create_campaign(
+                e.clone(),
+                500 * TOKEN_UNIT,
+                pair.clone(),
+                unlock,
+                100_000 * TOKEN_UNIT,
+                0,
+                None,
+                CampaignLimits::default(),
+                sponsor.clone(),
+            )
+        })
+        .unwrap()
+    };
+    let short = create(&usdc_eurc_pair, 10);
+    let long = create(&usdc_some_pair, 30);
+    for id in [short, long] {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), id, 1_000 * TOKEN_UNIT, alice.clone(), None)
+        })
+        .unwrap();
+    }
+    assert_eq!(
+        e.as_contract(&mgr, || get_user_camps(&e, &alice)),
+        Vec::from_array(&e, [short, long])
     );
 
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let res = e.as_contract(&mgr, || crate::FlashCampaignManager::claim_all(e.clone(), alice.clone()));
+    assert_eq!(res.len(), 2);
+    let (r0, r1) = (res.get(0).unwrap(), res.get(1).unwrap());
+    assert_eq!((r0.id, r0.status), (short, ClaimStatus::Claimed));
+    assert!(r0.flash > 0 && r0.lp > 0);
+    assert_eq!(token::Client::new(&e, &usdc_eurc_pair).balance(&alice), r0.lp);
+    assert_eq!(r1, ClaimResult { id: long, status: ClaimStatus::NotEnded, flash: 0, lp: 0 });
+
+    e.ledger().with_mut(|li| li.sequence_number += 20);
+    let claim_many = || {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::claim_many(
+                e.clone(),
+                Vec::from_array(&e, [short, long]),
+                alice.clone(),
+                cold.clone(),
+            )
+        })
+    };
 
-    // Deploy with empty constructor args (or adjust if your account contract requires args)
-    let deployed_account_address =  e.as_contract(&mgr, || {e.deployer().with_current_contract(user_salt.clone()).deploy_v2(account_wasm_hash.clone(), ())});
-    std::println!(
-        "[TEST_NATIVE] Deployed account contract at address: {:?}",
-        deployed_account_address
+    // a failing claim reports its own error and leaves the position alone
+    let c: Campaign = e.as_contract(&mgr, || load_camp(&e, long));
+    let mut broken = c.clone();
+    broken.reward_flash = i128::MAX;
+    e.as_contract(&mgr, || save_camp(&e, long, &broken));
+    let res = claim_many();
+    assert_eq!(res.get(0).unwrap().status, ClaimStatus::NothingToClaim);
+    assert_eq!(res.get(1).unwrap().status, ClaimStatus::Failed(FlashErr::Math as u32));
+    assert_eq!(
+        e.as_contract(&mgr, || crate::FlashCampaignManager::claim(e.clone(), long, alice.clone())),
+        Err(FlashErr::Math)
     );
+    e.as_contract(&mgr, || save_camp(&e, long, &c));
+
+    let res = claim_many();
+    assert_eq!(res.get(0).unwrap().status, ClaimStatus::NothingToClaim);
+    let r = res.get(1).unwrap();
+    assert_eq!(r.status, ClaimStatus::Claimed);
+    assert_eq!(flash.balance(&cold), r.flash);
+    assert_eq!(token::Client::new(&e, &usdc_some_pair).balance(&cold), r.lp);
+    assert!(e.as_contract(&mgr, || get_user_camps(&e, &alice)).is_empty());
+}
 
-    // Optionally: Add the manager and user as authorized signers if required by your account contract
-    //let account_client = account_contract::AccountClient::new(&e, &account_address);
-    // Example: account_client.add_signer(&mgr.address); account_client.add_signer(&alice);
-    // Uncomment and adjust the above if your contract requires explicit authorization setup
+#[test]
+fn test_keeper_distribution_native() {
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    std::println!("[TEST] ---- test_keeper_distribution_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_keeper_distribution_native");
+    let sponsor = users.get(0).unwrap();
+    let (alice, bob, carol) = (users.get(1).unwrap(), users.get(2).unwrap(), users.get(3).unwrap());
+    let keeper = Address::generate(&e);
+    let cold = Address::generate(&e);
 
-    // Join campaign as single user via smart account, natively (so logs are visible)
-    let join_amount = 2000 * TOKEN_UNIT;
-    let join_result = e.as_contract(&mgr, || {
-        crate::FlashCampaignManager::join_campaign_with_account(
+    let id = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::create_campaign(
             e.clone(),
-            campaign_id,
-            join_amount,
-            alice.clone(),
+            500 * TOKEN_UNIT,
+            usdc_eurc_pair.clone(),
+            10,
+            100_000 * TOKEN_UNIT,
+            0,
+            None,
+            CampaignLimits::default(),
+            sponsor.clone(),
         )
+    })
+    .unwrap();
+    let enable = |bps: u32, who: &Address| {
+        e.as_contract(&mgr, || crate::FlashCampaignManager::enable_distribution(e.clone(), id, bps, who.clone()))
+    };
+    let distribute = |start: u32, limit: u32| {
+        e.as_contract(&mgr, || crate::FlashCampaignManager::distribute(e.clone(), id, start, limit, keeper.clone()))
+    };
+    assert_eq!(distribute(0, 10), Err(FlashErr::DistributionOff));
+    assert_eq!(enable(50, &alice), Err(FlashErr::NotCreator));
+    assert_eq!(enable(MAX_KEEPER_BPS + 1, &sponsor), Err(FlashErr::BpsOutOfRange));
+    enable(50, &sponsor).unwrap();
+
+    for u in [&alice, &bob, &carol] {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), id, 500 * TOKEN_UNIT, u.clone(), None)
+        })
+        .unwrap();
+    }
+    e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::set_claim_recipient(e.clone(), alice.clone(), Some(cold.clone()))
     });
-    assert!(join_result.is_ok(), "User should be able to join the campaign via smart account");
-    std::println!("[TEST_NATIVE] User joined campaign via smart account: {:?}", join_result);
+    assert_eq!(distribute(0, 10), Err(FlashErr::TooEarly));
 
-    // Optionally: Check user position exists for smart account
-    // ...
+    // what a plain claim would pay each of them
+    let c: Campaign = e.as_contract(&mgr, || load_camp(&e, id));
+    let pos = |u: &Address| -> UserPos {
+        e.as_contract(&mgr, || e.storage().instance().get(&upos_key(&e, id, u)).unwrap())
+    };
+    let (pa, pb) = (pos(&alice), pos(&bob));
+    let gross = |up: &UserPos| c.reward_flash * up.weight / c.total_weight;
+
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let res = distribute(0, 2).unwrap();
+    assert_eq!(res.len(), 2);
+    let (ra, rb) = (res.get(0).unwrap(), res.get(1).unwrap());
+    assert_eq!((ra.status, rb.status), (ClaimStatus::Claimed, ClaimStatus::Claimed));
+    assert_eq!(flash.balance(&cold), ra.flash);
+    assert_eq!(token::Client::new(&e, &usdc_eurc_pair).balance(&cold), ra.lp);
+    assert_eq!(flash.balance(&alice), 0);
+    // 0.5 % of each FLASH share goes to the keeper, the LP is untouched
+    let cut_a = gross(&pa) * 50 / 10_000;
+    let cut_b = gross(&pb) * 50 / 10_000;
+    assert_eq!(ra.flash, gross(&pa) - cut_a);
+    assert_eq!(rb.flash, gross(&pb) - cut_b);
+    assert_eq!((ra.lp, rb.lp), (pa.lp_held, pb.lp_held));
+    let bounty = flash.balance(&keeper);
+    assert_eq!(bounty, cut_a + cut_b);
+
+    e.as_contract(&mgr, || crate::FlashCampaignManager::claim(e.clone(), id, carol.clone())).unwrap();
+    let res = distribute(2, 10).unwrap();
+    assert_eq!(res.len(), 1);
+    assert_eq!(res.get(0).unwrap().status, ClaimStatus::NothingToClaim);
+    assert_eq!(flash.balance(&keeper), bounty);
+    // offsets past the last rank are an empty page
+    assert_eq!(distribute(u32::MAX, 10).unwrap().len(), 0);
 }
+
+#[test]
+fn test_transfer_position_native() {
+    use soroban_sdk::testutils::Ledger;
+    std::println!("[TEST] ---- test_transfer_position_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_transfer_position_native");
+    let sponsor = users.get(0).unwrap();
+    let (alice, bob, carol) = (users.get(1).unwrap(), users.get(2).unwrap(), users.get(3).unwrap());
+
+    let id = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::create_campaign(
+            e.clone(),
+            500 * TOKEN_UNIT,
+            usdc_eurc_pair.clone(),
+            10,
+            100_000 * TOKEN_UNIT,
+            0,
+            None,
+            CampaignLimits::default(),
+            sponsor.clone(),
+        )
+    })
+    .unwrap();
+    for u in [&alice, &carol] {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), id, 500 * TOKEN_UNIT, u.clone(), None)
+        })
+        .unwrap();
+    }
+    let held = |who: &Address| e.as_contract(&mgr, || crate::FlashCampaignManager::positions_of(e.clone(), who.clone()));
+    let owner = |t: u64| e.as_contract(&mgr, || crate::FlashCampaignManager::owner_of(e.clone(), t));
+    let transfer = |from: &Address, to: &Address, t: u64| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::transfer_position(e.clone(), from.clone(), to.clone(), t)
+        })
+    };
+    let ta = held(&alice).get(0).unwrap();
+    let tc = held(&carol).get(0).unwrap();
+    assert_ne!(ta, tc);
+    assert_eq!(owner(ta), Some(alice.clone()));
+
+    let pos = |who: &Address| -> UserPos {
+        e.as_contract(&mgr, || e.storage().instance().get(&upos_key(&e, id, who)).unwrap())
+    };
+
+    assert_eq!(transfer(&bob, &carol, ta), Err(FlashErr::NotPositionOwner));
+    transfer(&alice, &bob, ta).unwrap();
+    assert_eq!(owner(ta), Some(bob.clone()));
+    assert!(held(&alice).is_empty());
+    assert_eq!(held(&bob), Vec::from_array(&e, [ta]));
+
+    // carol already has a position: the two merge under the earlier rank
+    let (pa, pc) = (pos(&bob), pos(&carol));
+    transfer(&carol, &bob, tc).unwrap();
+    assert_eq!(owner(tc), None);
+    assert_eq!(owner(ta), Some(bob.clone()));
+    assert!(held(&carol).is_empty());
+    assert_eq!(held(&bob), Vec::from_array(&e, [ta]));
+    let merged = pos(&bob);
+    assert_eq!(merged.rank, pa.rank);
+    assert_eq!((merged.lp, merged.weight), (pa.lp + pc.lp, pa.weight + pc.weight));
+    assert_eq!(merged.lp_held, pa.lp_held + pc.lp_held);
+
+    // a smart account hands its position on through its own entrypoint
+    let acct = e.register(account_contract::WASM, (alice.clone(), test_signer(&e, 1), Bytes::from_slice(&e, b"flashpool.app")));
+    let ac = AccountClient::new(&e, &acct);
+    let t0 = crate::pair::Client::new(&e, &usdc_eurc_pair).token_0();
+    ac.deposit(&alice, &t0, &(500 * TOKEN_UNIT));
+    ac.join_campaign(&mgr, &id, &t0, &(500 * TOKEN_UNIT));
+    let tx = held(&acct).get(0).unwrap();
+    ac.transfer_position(&mgr, &carol, &tx);
+    assert_eq!(owner(tx), Some(carol.clone()));
+    assert!(held(&acct).is_empty());
+
+    e.ledger().with_mut(|li| li.sequence_number += 10);
+    let claim = |who: &Address| e.as_contract(&mgr, || crate::FlashCampaignManager::claim(e.clone(), id, who.clone()));
+    assert_eq!(claim(&alice), Err(FlashErr::NothingToClaim));
+    claim(&bob).unwrap();
+    assert!(flash.balance(&bob) > 0);
+    assert_eq!(token::Client::new(&e, &usdc_eurc_pair).balance(&bob), merged.lp_held);
+    assert_eq!(owner(ta), None);
+    claim(&carol).unwrap();
+    assert_eq!(owner(tx), None);
+}
+
+#[test]
+fn test_position_approvals_native() {
+    use soroban_sdk::testutils::Address as _;
+    std::println!("[TEST] ---- test_position_approvals_native ----");
+    let (
+        e,
+        mgr,
+        _god,
+        users,
+        _flash,
+        _usdc,
+        _eurc,
+        _some,
+        _factory_addr,
+        _flash_usdc_pair,
+        usdc_eurc_pair,
+        _usdc_some_pair,
+    ) = fresh_env_native("test_position_approvals_native");
+    let sponsor = users.get(0).unwrap();
+    let (alice, bob, carol) = (users.get(1).unwrap(), users.get(2).unwrap(), users.get(3).unwrap());
+    let market = Address::generate(&e);
+
+    let id = e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::create_campaign(
+            e.clone(),
+            500 * TOKEN_UNIT,
+            usdc_eurc_pair.clone(),
+            10,
+            100_000 * TOKEN_UNIT,
+            0,
+            None,
+            CampaignLimits::default(),
+            sponsor.clone(),
+        )
+    })
+    .unwrap();
+    for u in [&alice, &carol] {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::join_campaign(e.clone(), id, 500 * TOKEN_UNIT, u.clone(), None)
+        })
+        .unwrap();
+    }
+    let held = |who: &Address| e.as_contract(&mgr, || crate::FlashCampaignManager::positions_of(e.clone(), who.clone()));
+    let owner = |t: u64| e.as_contract(&mgr, || crate::FlashCampaignManager::owner_of(e.clone(), t));
+    let approved = |t: u64| e.as_contract(&mgr, || crate::FlashCampaignManager::get_approved(e.clone(), t));
+    let approve = |who: &Address, spender: Option<Address>, t: u64| {
+        e.as_contract(&mgr, || crate::FlashCampaignManager::approve_position(e.clone(), who.clone(), spender, t))
+    };
+    let operator = |who: &Address, op: &Address, on: bool| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::set_position_operator(e.clone(), who.clone(), op.clone(), on)
+        })
+    };
+    let transfer_from = |spender: &Address, from: &Address, to: &Address, t: u64| {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::transfer_position_from(e.clone(), spender.clone(), from.clone(), to.clone(), t)
+        })
+    };
+    let ta = held(&alice).get(0).unwrap();
+    let tc = held(&carol).get(0).unwrap();
+
+    // only the owner approves, and nobody else moves a position unapproved
+    assert_eq!(transfer_from(&market, &alice, &market, ta), Err(FlashErr::NotApproved));
+    assert_eq!(approve(&bob, Some(market.clone()), ta), Err(FlashErr::NotPositionOwner));
+    approve(&alice, Some(bob.clone()), ta).unwrap();
+    approve(&alice, None, ta).unwrap();
+    assert_eq!(approved(ta), None);
+    assert_eq!(transfer_from(&bob, &alice, &bob, ta), Err(FlashErr::NotApproved));
+
+    // an approved market takes custody without alice signing the transfer
+    approve(&alice, Some(market.clone()), ta).unwrap();
+    assert_eq!(approved(ta), Some(market.clone()));
+    transfer_from(&market, &alice, &market, ta).unwrap();
+    assert_eq!(owner(ta), Some(market.clone()));
+    assert_eq!(approved(ta), None);
+
+    // the approval went with the transfer: back with alice it needs a new one
+    e.as_contract(&mgr, || {
+        crate::FlashCampaignManager::transfer_position(e.clone(), market.clone(), alice.clone(), ta)
+    })
+    .unwrap();
+    assert_eq!(approved(ta), None);
+    assert_eq!(transfer_from(&market, &alice, &market, ta), Err(FlashErr::NotApproved));
+
+    // an operator moves any of carol's positions until she revokes it
+    let is_operator = || {
+        e.as_contract(&mgr, || {
+            crate::FlashCampaignManager::is_position_operator(e.clone(), carol.clone(), market.clone())
+        })
+    };
+    operator(&carol, &market, true);
+    assert!(is_operator());
+    transfer_from(&market, &carol, &bob, tc).unwrap();
+    assert_eq!(owner(tc), Some(bob.clone()));
+    assert_eq!(transfer_from(&market, &bob, &market, tc), Err(FlashErr::NotApproved));
+    operator(&carol, &market, false);
+    assert!(!is_operator());
+}
+
 /*
 #[test]
 fn test_create_and_join_campaign_two_users() {
@@ -0,0 +1,182 @@
+//! session_policy
+//! -------------------------------------------------------------
+//! Policy signer for short-lived session keys on a smart wallet.
+//!
+//! A session is an ed25519 signer stored with `SignerStorage::Temporary` and
+//! a near `SignerExpiration`, whose `SignerLimits` point every contract it may
+//! touch at this policy (see `session_signer`). The policy then only lets it
+//! call the campaign manager's `join_campaign` and `claim`, and caps what it
+//! may `transfer` of each token over the whole session. Sessions end at the
+//! same ledger as their signer and live in temporary storage until then, so
+//! neither outlives the other.
+
+#![no_std]
+
+use smart_wallet_interface::{
+    types::{Signer, SignerExpiration, SignerKey, SignerLimits, SignerStorage},
+    PolicyInterface,
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short, Address,
+    BytesN, Env, Map, Symbol, TryFromVal, Vec,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    NotAllowed = 1,
+    NoSession = 2,
+    CapExceeded = 3,
+    InvalidCap = 4,
+    InvalidExpiration = 5,
+}
+
+/// What one session key of one wallet may do, and how much it has spent.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub manager: Address,
+    pub expiration: u32,           // last ledger, as the signer's `SignerExpiration`
+    pub caps: Map<Address, i128>,  // token -> most it may transfer in total
+    pub spent: Map<Address, i128>, // token -> transferred so far
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Session(Address, SignerKey), // (wallet, session signer)
+}
+
+/// The session signer for `public_key`: temporary, expiring at `expiration`,
+/// and limited to `manager` and `tokens`, each guarded by `policy`.
+pub fn session_signer(
+    env: &Env,
+    public_key: BytesN<32>,
+    expiration: u32,
+    manager: &Address,
+    tokens: &Vec<Address>,
+    policy: &Address,
+) -> Signer {
+    let guard = Some(Vec::from_array(env, [SignerKey::Policy(policy.clone())]));
+    let mut limits = Map::new(env);
+    limits.set(manager.clone(), guard.clone());
+    for token in tokens.iter() {
+        limits.set(token, guard.clone());
+    }
+    Signer::Ed25519(
+        public_key,
+        SignerExpiration(Some(expiration)),
+        SignerLimits(Some(limits)),
+        SignerStorage::Temporary,
+    )
+}
+
+fn load_session(env: &Env, wallet: &Address, signer: &SignerKey) -> Option<Session> {
+    env.storage()
+        .temporary()
+        .get::<DataKey, Session>(&DataKey::Session(wallet.clone(), signer.clone()))
+        .filter(|session| env.ledger().sequence() <= session.expiration)
+}
+
+/// Stores `session` and keeps it alive through its last ledger.
+fn store_session(env: &Env, wallet: Address, signer: SignerKey, session: &Session) {
+    let key = DataKey::Session(wallet, signer);
+    let live_for = (session.expiration - env.ledger().sequence()).min(env.storage().max_ttl());
+    env.storage().temporary().set(&key, session);
+    env.storage().temporary().extend_ttl(&key, live_for, live_for);
+}
+
+#[contract]
+pub struct Contract;
+
+#[contractimpl]
+impl Contract {
+    /// Opens (or resets) `signer`'s session on `wallet` with fresh spend caps.
+    /// `expiration` should match the one given to `session_signer`.
+    pub fn set_session(
+        env: Env,
+        wallet: Address,
+        signer: SignerKey,
+        manager: Address,
+        caps: Map<Address, i128>,
+        expiration: u32,
+    ) -> Result<(), Error> {
+        wallet.require_auth();
+        if caps.values().iter().any(|cap| cap < 0) {
+            return Err(Error::InvalidCap);
+        }
+        if expiration < env.ledger().sequence() {
+            return Err(Error::InvalidExpiration);
+        }
+        let session = Session {
+            manager,
+            expiration,
+            caps,
+            spent: Map::new(&env),
+        };
+        store_session(&env, wallet, signer, &session);
+        Ok(())
+    }
+
+    pub fn remove_session(env: Env, wallet: Address, signer: SignerKey) {
+        wallet.require_auth();
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Session(wallet, signer));
+    }
+
+    pub fn session(env: Env, wallet: Address, signer: SignerKey) -> Option<Session> {
+        load_session(&env, &wallet, &signer)
+    }
+}
+
+#[contractimpl]
+impl PolicyInterface for Contract {
+    fn policy__(env: Env, source: Address, signer: SignerKey, contexts: Vec<Context>) {
+        // only the wallet's own __check_auth may spend its caps
+        source.require_auth();
+
+        let mut session = load_session(&env, &source, &signer)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NoSession));
+
+        for context in contexts.iter() {
+            let Context::Contract(ContractContext {
+                contract,
+                fn_name,
+                args,
+            }) = context
+            else {
+                panic_with_error!(&env, Error::NotAllowed)
+            };
+
+            if contract == session.manager {
+                if fn_name != Symbol::new(&env, "join_campaign") && fn_name != symbol_short!("claim") {
+                    panic_with_error!(&env, Error::NotAllowed)
+                }
+            } else if let Some(cap) = session.caps.get(contract.clone()) {
+                // transfer(from, to, amount)
+                let amount = args
+                    .get(2)
+                    .and_then(|v| i128::try_from_val(&env, &v).ok())
+                    .unwrap_or_else(|| panic_with_error!(&env, Error::NotAllowed));
+                if fn_name != symbol_short!("transfer") || amount < 0 {
+                    panic_with_error!(&env, Error::NotAllowed)
+                }
+                let spent = session.spent.get(contract.clone()).unwrap_or(0) + amount;
+                if spent > cap {
+                    panic_with_error!(&env, Error::CapExceeded)
+                }
+                session.spent.set(contract, spent);
+            } else {
+                panic_with_error!(&env, Error::NotAllowed)
+            }
+        }
+
+        store_session(&env, source, signer, &session);
+    }
+}
+
+#[cfg(test)]
+mod test;
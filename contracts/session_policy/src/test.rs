@@ -0,0 +1,195 @@
+#![cfg(test)]
+extern crate std;
+
+use crate::{session_signer, Contract, ContractClient, Error, Session};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer as _};
+use smart_wallet::{Contract as Wallet, ContractClient as WalletClient};
+use smart_wallet_interface::types::{
+    Error as WalletError, Signature, Signatures, Signer, SignerExpiration, SignerKey, SignerLimits,
+    SignerStorage,
+};
+use soroban_sdk::{
+    auth::{Context, ContractContext},
+    map,
+    testutils::{storage::Temporary as _, Address as _, Ledger},
+    vec, Address, BytesN, Env, IntoVal, Symbol, Vec,
+};
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+fn call(e: &Env, contract: &Address, fn_name: &str, args: soroban_sdk::Vec<soroban_sdk::Val>) -> Context {
+    Context::Contract(ContractContext {
+        contract: contract.clone(),
+        fn_name: Symbol::new(e, fn_name),
+        args,
+    })
+}
+
+struct Setup {
+    e: Env,
+    wallet: Address,
+    policy: ContractClient<'static>,
+    manager: Address,
+    token: Address,
+    session: Keypair,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let admin = keypair(1);
+        let wallet = e.register(
+            Wallet,
+            (Signer::Ed25519(
+                BytesN::from_array(&e, &admin.public.to_bytes()),
+                SignerExpiration(None),
+                SignerLimits(None),
+                SignerStorage::Persistent,
+            ),),
+        );
+        let policy = ContractClient::new(&e, &e.register(Contract, ()));
+        let manager = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        let session = keypair(2);
+        let session_pk = BytesN::from_array(&e, &session.public.to_bytes());
+        let expiration = e.ledger().sequence() + 100;
+        WalletClient::new(&e, &wallet).add_signer(&session_signer(
+            &e,
+            session_pk.clone(),
+            expiration,
+            &manager,
+            &vec![&e, token.clone()],
+            &policy.address,
+        ));
+        policy.set_session(
+            &wallet,
+            &SignerKey::Ed25519(session_pk),
+            &manager,
+            &map![&e, (token.clone(), 1_000)],
+            &expiration,
+        );
+
+        Setup { e, wallet, policy, manager, token, session }
+    }
+
+    fn session_key(&self) -> SignerKey {
+        SignerKey::Ed25519(BytesN::from_array(&self.e, &self.session.public.to_bytes()))
+    }
+
+    /// Runs the wallet's `__check_auth` for `contexts`, signed by the session key.
+    fn check(&self, contexts: Vec<Context>) -> Result<(), soroban_sdk::Error> {
+        let payload = BytesN::from_array(&self.e, &[7; 32]);
+        let sig = self.session.sign(&payload.to_array()).to_bytes();
+        let signatures = Signatures(map![
+            &self.e,
+            (self.session_key(), Signature::Ed25519(BytesN::from_array(&self.e, &sig)))
+        ]);
+        self.e
+            .try_invoke_contract_check_auth::<soroban_sdk::Error>(
+                &self.wallet,
+                &payload,
+                signatures.into_val(&self.e),
+                &contexts,
+            )
+            .map_err(|err| err.unwrap())
+    }
+
+    fn join(&self, amount: i128) -> Vec<Context> {
+        let e = &self.e;
+        vec![
+            e,
+            call(e, &self.manager, "join_campaign", vec![e, 0u32.into_val(e), amount.into_val(e), self.wallet.into_val(e)]),
+            call(e, &self.token, "transfer", vec![e, self.wallet.into_val(e), self.manager.into_val(e), amount.into_val(e)]),
+        ]
+    }
+}
+
+#[test]
+fn test_session_joins_and_claims_within_cap() {
+    let s = Setup::new();
+    assert_eq!(s.check(s.join(600)), Ok(()));
+    assert_eq!(s.check(s.join(400)), Ok(()));
+    let claim = vec![&s.e, call(&s.e, &s.manager, "claim", vec![&s.e, 0u32.into_val(&s.e), s.wallet.into_val(&s.e)])];
+    assert_eq!(s.check(claim), Ok(()));
+
+    let session: Session = s.policy.session(&s.wallet, &s.session_key()).unwrap();
+    assert_eq!(session.spent.get(s.token.clone()), Some(1_000));
+}
+
+#[test]
+fn test_session_cap_is_cumulative() {
+    let s = Setup::new();
+    assert_eq!(s.check(s.join(600)), Ok(()));
+    assert_eq!(s.check(s.join(500)), Err(Error::CapExceeded.into()));
+    // resetting the session restores the allowance
+    s.policy.set_session(&s.wallet, &s.session_key(), &s.manager, &map![&s.e, (s.token.clone(), 1_000)], &100);
+    assert_eq!(s.check(s.join(500)), Ok(()));
+}
+
+#[test]
+fn test_session_is_scoped() {
+    let s = Setup::new();
+    let e = &s.e;
+    // other manager entrypoints
+    let withdraw = vec![e, call(e, &s.manager, "withdraw_pledge", vec![e, 0u32.into_val(e), s.wallet.into_val(e)])];
+    assert_eq!(s.check(withdraw), Err(Error::NotAllowed.into()));
+    // anything but transfer on a capped token
+    let approve = vec![e, call(e, &s.token, "approve", vec![e, s.wallet.into_val(e), s.manager.into_val(e), 1i128.into_val(e), 0u32.into_val(e)])];
+    assert_eq!(s.check(approve), Err(Error::NotAllowed.into()));
+    // contracts outside the signer's limits
+    let other = Address::generate(e);
+    let elsewhere = vec![e, call(e, &other, "transfer", vec![e, s.wallet.into_val(e), other.into_val(e), 1i128.into_val(e)])];
+    assert_eq!(s.check(elsewhere), Err(WalletError::MissingContext.into()));
+}
+
+#[test]
+fn test_session_expires() {
+    let s = Setup::new();
+    s.e.ledger().with_mut(|li| li.sequence_number += 101);
+    assert_eq!(s.check(s.join(1)), Err(WalletError::SignerExpired.into()));
+}
+
+#[test]
+fn test_removed_session_is_refused() {
+    let s = Setup::new();
+    s.policy.remove_session(&s.wallet, &s.session_key());
+    assert_eq!(s.check(s.join(1)), Err(Error::NoSession.into()));
+}
+
+#[test]
+fn test_negative_cap_rejected() {
+    let s = Setup::new();
+    assert_eq!(
+        s.policy.try_set_session(&s.wallet, &s.session_key(), &s.manager, &map![&s.e, (s.token.clone(), -1)], &100),
+        Err(Ok(Error::InvalidCap))
+    );
+}
+
+#[test]
+fn test_session_storage_follows_signer() {
+    let s = Setup::new();
+    let e = &s.e;
+    let key = crate::DataKey::Session(s.wallet.clone(), s.session_key());
+    let ttl = || e.as_contract(&s.policy.address, || e.storage().temporary().get_ttl(&key));
+    assert_eq!(ttl(), 100);
+
+    // each use keeps it alive to the end, but no further
+    e.ledger().with_mut(|li| li.sequence_number += 40);
+    assert_eq!(s.check(s.join(1)), Ok(()));
+    assert_eq!(ttl(), 60);
+    assert_eq!(
+        s.policy.try_set_session(&s.wallet, &s.session_key(), &s.manager, &map![e, (s.token.clone(), 1)], &39),
+        Err(Ok(Error::InvalidExpiration))
+    );
+
+    // gone along with the signer
+    e.ledger().with_mut(|li| li.sequence_number += 61);
+    assert_eq!(s.policy.session(&s.wallet, &s.session_key()), None);
+}